@@ -1,3 +1,4 @@
+use crate::core::dependency::EcosystemType;
 use clap::{Parser, Subcommand, ValueEnum};
 use std::path::PathBuf;
 
@@ -21,9 +22,9 @@ pub enum Commands {
         #[arg(short, long, default_value = ".")]
         repo: PathBuf,
 
-        /// Output format
-        #[arg(short, long, value_enum, default_value = "text")]
-        format: OutputFormat,
+        /// Output format (defaults to the config's `format`, then text)
+        #[arg(short, long, value_enum)]
+        format: Option<OutputFormat>,
 
         /// Skip git history analysis
         #[arg(long)]
@@ -32,6 +33,22 @@ pub enum Commands {
         /// Skip usage scanning
         #[arg(long)]
         no_scan: bool,
+
+        /// Ignore and don't update the analysis cache in the git directory
+        #[arg(long)]
+        no_cache: bool,
+
+        /// Dependency file to analyze, relative to the repository (skips detection)
+        #[arg(short, long)]
+        manifest: Option<PathBuf>,
+
+        /// Only consider this ecosystem, or parse --manifest as this ecosystem
+        #[arg(short, long, value_enum)]
+        ecosystem: Option<EcosystemArg>,
+
+        /// Read manifests and sources from this commit, branch or tag instead of the working directory
+        #[arg(long)]
+        rev: Option<String>,
     },
 
     /// List all dependencies in the project
@@ -40,9 +57,172 @@ pub enum Commands {
         #[arg(short, long, default_value = ".")]
         repo: PathBuf,
 
-        /// Output format
-        #[arg(short, long, value_enum, default_value = "text")]
-        format: OutputFormat,
+        /// Output format (defaults to the config's `format`, then text)
+        #[arg(short, long, value_enum)]
+        format: Option<OutputFormat>,
+
+        /// Dependency file to analyze, relative to the repository (skips detection)
+        #[arg(short, long)]
+        manifest: Option<PathBuf>,
+
+        /// Only consider this ecosystem, or parse --manifest as this ecosystem
+        #[arg(short, long, value_enum)]
+        ecosystem: Option<EcosystemArg>,
+
+        /// Read manifests and sources from this commit, branch or tag instead of the working directory
+        #[arg(long)]
+        rev: Option<String>,
+
+        /// List the dependencies shipped in this release tag
+        #[arg(long, conflicts_with = "rev")]
+        at_tag: Option<String>,
+    },
+
+    /// Audit every dependency: whether it is used and which commit introduced it
+    Audit {
+        /// Path to the repository
+        #[arg(short, long, default_value = ".")]
+        repo: PathBuf,
+
+        /// Output format (defaults to the config's `format`, then text)
+        #[arg(short, long, value_enum)]
+        format: Option<OutputFormat>,
+
+        /// Skip git history analysis
+        #[arg(long)]
+        no_git: bool,
+
+        /// Skip usage scanning
+        #[arg(long)]
+        no_scan: bool,
+
+        /// Ignore and don't update the analysis cache in the git directory
+        #[arg(long)]
+        no_cache: bool,
+
+        /// Dependency file to audit, relative to the repository (skips detection)
+        #[arg(short, long)]
+        manifest: Option<PathBuf>,
+
+        /// Only consider this ecosystem, or parse --manifest as this ecosystem
+        #[arg(short, long, value_enum)]
+        ecosystem: Option<EcosystemArg>,
+
+        /// Read manifests and sources from this commit, branch or tag instead of the working directory
+        #[arg(long)]
+        rev: Option<String>,
+    },
+
+    /// List dependencies that were removed, with when and why
+    Graveyard {
+        /// Path to the repository
+        #[arg(short, long, default_value = ".")]
+        repo: PathBuf,
+
+        /// Output format (defaults to the config's `format`, then text)
+        #[arg(short, long, value_enum)]
+        format: Option<OutputFormat>,
+
+        /// Ignore and don't update the analysis cache in the git directory
+        #[arg(long)]
+        no_cache: bool,
+
+        /// Dependency file to inspect, relative to the repository (skips detection)
+        #[arg(short, long)]
+        manifest: Option<PathBuf>,
+
+        /// Only consider this ecosystem, or parse --manifest as this ecosystem
+        #[arg(short, long, value_enum)]
+        ecosystem: Option<EcosystemArg>,
+
+        /// Look at the history up to this commit, branch or tag instead of HEAD
+        #[arg(long)]
+        rev: Option<String>,
+    },
+
+    /// Count the dependencies each author introduced, left unused and removed
+    Authors {
+        /// Path to the repository
+        #[arg(short, long, default_value = ".")]
+        repo: PathBuf,
+
+        /// Output format (defaults to the config's `format`, then text)
+        #[arg(short, long, value_enum)]
+        format: Option<OutputFormat>,
+
+        /// Group by person, or by the teams of the config's `[teams]` table
+        #[arg(long, value_enum, default_value = "person")]
+        by: AuthorGrouping,
+
+        /// Skip usage scanning, leaving unused counts at zero
+        #[arg(long)]
+        no_scan: bool,
+
+        /// Ignore and don't update the analysis cache in the git directory
+        #[arg(long)]
+        no_cache: bool,
+
+        /// Dependency file to inspect, relative to the repository (skips detection)
+        #[arg(short, long)]
+        manifest: Option<PathBuf>,
+
+        /// Only consider this ecosystem, or parse --manifest as this ecosystem
+        #[arg(short, long, value_enum)]
+        ecosystem: Option<EcosystemArg>,
+    },
+
+    /// Show dependency changes between two git revisions
+    Diff {
+        /// Older revision (commit, branch or tag)
+        from: String,
+
+        /// Newer revision (commit, branch or tag)
+        to: String,
+
+        /// Path to the repository
+        #[arg(short, long, default_value = ".")]
+        repo: PathBuf,
+
+        /// Output format (defaults to the config's `format`, then text)
+        #[arg(short, long, value_enum)]
+        format: Option<DiffFormat>,
+
+        /// Dependency file to compare, relative to the repository (skips detection)
+        #[arg(short, long)]
+        manifest: Option<PathBuf>,
+
+        /// Only consider this ecosystem, or parse --manifest as this ecosystem
+        #[arg(short, long, value_enum)]
+        ecosystem: Option<EcosystemArg>,
+    },
+
+    /// Record why dependencies exist, beyond their commit messages
+    Explain {
+        #[command(subcommand)]
+        action: ExplainAction,
+    },
+
+    /// Install pre-commit and commit-msg hooks that require a reason for new dependencies
+    InstallHooks {
+        /// Path to the repository
+        #[arg(short, long, default_value = ".")]
+        repo: PathBuf,
+
+        /// Replace existing hooks that were not installed by dependency-blame
+        #[arg(long)]
+        force: bool,
+    },
+
+    /// Entry points run by the installed git hooks
+    Hook {
+        #[command(subcommand)]
+        action: HookAction,
+    },
+
+    /// Manage the analysis cache
+    Cache {
+        #[command(subcommand)]
+        action: CacheAction,
     },
 
     /// Interactive TUI mode
@@ -53,6 +233,67 @@ pub enum Commands {
     },
 }
 
+#[derive(Subcommand)]
+pub enum CacheAction {
+    /// Delete the cached history index and import results
+    Clear {
+        /// Path to the repository
+        #[arg(short, long, default_value = ".")]
+        repo: PathBuf,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ExplainAction {
+    /// Record the reason for a dependency in DEPENDENCIES.toml, or in git notes
+    Set {
+        /// Name of the dependency
+        dependency: String,
+
+        /// Why the dependency is needed
+        reason: String,
+
+        /// Path to the repository
+        #[arg(short, long, default_value = ".")]
+        repo: PathBuf,
+
+        /// Attach the reason as a git note (refs/notes/dependency-blame) to the
+        /// commit that introduced the dependency, instead of DEPENDENCIES.toml
+        #[arg(long)]
+        notes: bool,
+
+        /// Only give the reason to this ecosystem's dependency of the name,
+        /// under an `[<ecosystem>]` table
+        #[arg(short, long, value_enum)]
+        ecosystem: Option<EcosystemArg>,
+    },
+    /// Check that DEPENDENCIES.toml only lists declared dependencies
+    Check {
+        /// Path to the repository
+        #[arg(short, long, default_value = ".")]
+        repo: PathBuf,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum HookAction {
+    /// List the dependencies the staged changes add
+    PreCommit {
+        /// Path to the repository
+        #[arg(short, long, default_value = ".")]
+        repo: PathBuf,
+    },
+    /// Reject a commit message that gives no reason for a new dependency
+    CommitMsg {
+        /// File holding the commit message, as passed by git
+        message_file: PathBuf,
+
+        /// Path to the repository
+        #[arg(short, long, default_value = ".")]
+        repo: PathBuf,
+    },
+}
+
 #[derive(Copy, Clone, PartialEq, Eq, ValueEnum)]
 pub enum OutputFormat {
     /// Human-readable text
@@ -60,3 +301,47 @@ pub enum OutputFormat {
     /// JSON output
     Json,
 }
+
+#[derive(Copy, Clone, PartialEq, Eq, ValueEnum)]
+pub enum DiffFormat {
+    /// Human-readable text
+    Text,
+    /// JSON output
+    Json,
+    /// Markdown, for release notes
+    Markdown,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, ValueEnum)]
+pub enum AuthorGrouping {
+    /// Commit author, after `.mailmap`
+    Person,
+    /// Team of the author's email, from the config's `[teams]` table
+    Team,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, ValueEnum)]
+pub enum EcosystemArg {
+    /// Cargo.toml
+    Rust,
+    /// package.json
+    Node,
+    /// requirements.txt, pyproject.toml
+    Python,
+    /// go.mod
+    Go,
+    /// deno.json, deno.jsonc
+    Deno,
+}
+
+impl From<EcosystemArg> for EcosystemType {
+    fn from(arg: EcosystemArg) -> Self {
+        match arg {
+            EcosystemArg::Rust => EcosystemType::Rust,
+            EcosystemArg::Node => EcosystemType::Node,
+            EcosystemArg::Python => EcosystemType::Python,
+            EcosystemArg::Go => EcosystemType::Go,
+            EcosystemArg::Deno => EcosystemType::Deno,
+        }
+    }
+}
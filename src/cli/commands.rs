@@ -1,9 +1,17 @@
-use crate::cli::args::{Cli, Commands, OutputFormat};
-use crate::core::dependency::DependencyQuery;
-use crate::core::error::Result;
+use crate::cli::args::{
+    AuthorGrouping, CacheAction, Cli, Commands, DiffFormat, ExplainAction, HookAction, OutputFormat,
+};
+use crate::core::cache::AnalysisCache;
+use crate::core::config::Config;
+use crate::core::dependency::{Dependency, DependencyQuery};
+use crate::core::error::{DependencyBlameError, Result};
+use crate::core::hooks;
 use crate::core::orchestrator::DependencyOrchestrator;
+use crate::core::rationale::{self, RationaleSource};
 use crate::ecosystems::registry::create_default_registry;
-use crate::presentation::{json, text, tui};
+use crate::presentation::{json, markdown, text, tui};
+use clap::ValueEnum;
+use std::path::Path;
 
 pub fn execute(cli: Cli) -> Result<()> {
     match cli.command {
@@ -13,11 +21,21 @@ pub fn execute(cli: Cli) -> Result<()> {
             format,
             no_git,
             no_scan,
+            no_cache,
+            manifest,
+            ecosystem,
+            rev,
         } => {
+            let config = Config::load(&repo)?;
+            let format = resolve_format(format, &config)?;
             let registry = create_default_registry();
-            let orchestrator = DependencyOrchestrator::new(registry);
+            let orchestrator = DependencyOrchestrator::new(registry).with_config(config);
 
-            let query = DependencyQuery::with_options(dependency, repo, !no_git, !no_scan);
+            let mut query = DependencyQuery::with_options(dependency, repo, !no_git, !no_scan);
+            query.manifest = manifest;
+            query.ecosystem = ecosystem.map(Into::into);
+            query.use_cache = !no_cache;
+            query.revision = rev;
 
             let analysis = orchestrator.analyze(query)?;
 
@@ -33,11 +51,27 @@ pub fn execute(cli: Cli) -> Result<()> {
             Ok(())
         }
 
-        Commands::List { repo, format } => {
+        Commands::List {
+            repo,
+            format,
+            manifest,
+            ecosystem,
+            rev,
+            at_tag,
+        } => {
+            let config = Config::load(&repo)?;
+            let format = resolve_format(format, &config)?;
             let registry = create_default_registry();
-            let orchestrator = DependencyOrchestrator::new(registry);
+            let orchestrator = DependencyOrchestrator::new(registry).with_config(config);
 
-            let dependencies = orchestrator.list_all_dependencies(&repo)?;
+            // Only a tag will do, not a branch or commit of the same name
+            let rev = at_tag.map(|tag| format!("refs/tags/{}", tag)).or(rev);
+            let dependencies = orchestrator.list_dependencies(
+                &repo,
+                manifest.as_deref(),
+                ecosystem.map(Into::into),
+                rev.as_deref(),
+            )?;
 
             match format {
                 OutputFormat::Text => {
@@ -51,9 +85,297 @@ pub fn execute(cli: Cli) -> Result<()> {
             Ok(())
         }
 
+        Commands::Audit {
+            repo,
+            format,
+            no_git,
+            no_scan,
+            no_cache,
+            manifest,
+            ecosystem,
+            rev,
+        } => {
+            let config = Config::load(&repo)?;
+            let format = resolve_format(format, &config)?;
+            let registry = create_default_registry();
+            let orchestrator = DependencyOrchestrator::new(registry)
+                .with_config(config)
+                .with_cache(!no_cache);
+
+            let analyses = orchestrator.audit(
+                &repo,
+                manifest.as_deref(),
+                ecosystem.map(Into::into),
+                rev.as_deref(),
+                !no_git,
+                !no_scan,
+            )?;
+
+            match format {
+                OutputFormat::Text => text::print_audit(&analyses, !no_scan),
+                OutputFormat::Json => json::print_audit(&analyses)?,
+            }
+
+            Ok(())
+        }
+
+        Commands::Graveyard {
+            repo,
+            format,
+            no_cache,
+            manifest,
+            ecosystem,
+            rev,
+        } => {
+            let config = Config::load(&repo)?;
+            let format = resolve_format(format, &config)?;
+            let registry = create_default_registry();
+            let orchestrator = DependencyOrchestrator::new(registry)
+                .with_config(config)
+                .with_cache(!no_cache);
+
+            let removed = orchestrator.graveyard(
+                &repo,
+                manifest.as_deref(),
+                ecosystem.map(Into::into),
+                rev.as_deref(),
+            )?;
+
+            match format {
+                OutputFormat::Text => text::print_graveyard(&removed),
+                OutputFormat::Json => json::print_graveyard(&removed)?,
+            }
+
+            Ok(())
+        }
+
+        Commands::Authors {
+            repo,
+            format,
+            by,
+            no_scan,
+            no_cache,
+            manifest,
+            ecosystem,
+        } => {
+            let config = Config::load(&repo)?;
+            let format = resolve_format(format, &config)?;
+            let registry = create_default_registry();
+            let orchestrator = DependencyOrchestrator::new(registry)
+                .with_config(config)
+                .with_cache(!no_cache);
+
+            let authors = orchestrator.authors(
+                &repo,
+                manifest.as_deref(),
+                ecosystem.map(Into::into),
+                by == AuthorGrouping::Team,
+                !no_scan,
+            )?;
+
+            match format {
+                OutputFormat::Text => text::print_authors(&authors, !no_scan),
+                OutputFormat::Json => json::print_authors(&authors)?,
+            }
+
+            Ok(())
+        }
+
+        Commands::Diff {
+            from,
+            to,
+            repo,
+            format,
+            manifest,
+            ecosystem,
+        } => {
+            let config = Config::load(&repo)?;
+            let format = resolve_format(format, &config)?;
+            let registry = create_default_registry();
+            let orchestrator = DependencyOrchestrator::new(registry).with_config(config);
+
+            let diff = orchestrator.diff(
+                &repo,
+                &from,
+                &to,
+                manifest.as_deref(),
+                ecosystem.map(Into::into),
+            )?;
+
+            match format {
+                DiffFormat::Text => text::print_diff(&diff),
+                DiffFormat::Json => json::print_diff(&diff)?,
+                DiffFormat::Markdown => markdown::print_diff(&diff),
+            }
+
+            Ok(())
+        }
+
+        Commands::Explain {
+            action:
+                ExplainAction::Set {
+                    dependency,
+                    reason,
+                    repo,
+                    notes,
+                    ecosystem,
+                },
+        } => {
+            let config = Config::load(&repo)?;
+            let orchestrator =
+                DependencyOrchestrator::new(create_default_registry()).with_config(config);
+            match orchestrator.record_rationale(
+                &repo,
+                ecosystem.map(Into::into),
+                &dependency,
+                &reason,
+                notes,
+            )? {
+                RationaleSource::Sidecar { path } => {
+                    println!("Recorded the reason for {} in {}", dependency, path.display())
+                }
+                RationaleSource::Note { commit } => println!(
+                    "Recorded the reason for {} in a note on {} ({}); push it with `git push origin {}`",
+                    dependency,
+                    &commit[..8],
+                    rationale::NOTES_REF,
+                    rationale::NOTES_REF
+                ),
+            }
+            Ok(())
+        }
+
+        Commands::Explain {
+            action: ExplainAction::Check { repo },
+        } => {
+            let config = Config::load(&repo)?;
+            let orchestrator =
+                DependencyOrchestrator::new(create_default_registry()).with_config(config);
+            match orchestrator.stale_rationales(&repo)? {
+                None => println!("No {} to check", rationale::SIDECAR_FILE),
+                Some(stale) if stale.is_empty() => println!(
+                    "{} only lists declared dependencies",
+                    rationale::SIDECAR_FILE
+                ),
+                Some(stale) => {
+                    return Err(DependencyBlameError::Other(format!(
+                        "{} lists dependencies that are not declared any more: {}",
+                        rationale::SIDECAR_FILE,
+                        stale.join(", ")
+                    )))
+                }
+            }
+            Ok(())
+        }
+
+        Commands::InstallHooks { repo, force } => {
+            for path in hooks::install(&repo, force)? {
+                println!("Installed {}", path.display());
+            }
+            Ok(())
+        }
+
+        Commands::Hook {
+            action: HookAction::PreCommit { repo },
+        } => {
+            let (_, added) = staged_additions(&repo)?;
+            if !added.is_empty() {
+                text::print_hook_notice(&added);
+            }
+            Ok(())
+        }
+
+        Commands::Hook {
+            action: HookAction::CommitMsg { message_file, repo },
+        } => {
+            let (config, added) = staged_additions(&repo)?;
+            if added.is_empty() {
+                return Ok(());
+            }
+            let message = hooks::read_commit_message(&repo, &message_file)?;
+            let unexplained = hooks::unexplained(&message, &added);
+            if unexplained.is_empty() {
+                return Ok(());
+            }
+
+            let enforced = config.hooks.enforce.unwrap_or(true);
+            text::print_hook_rejection(&unexplained, enforced);
+            if enforced {
+                return Err(DependencyBlameError::MissingDependencyReason(
+                    unexplained.iter().map(|dep| dep.name.clone()).collect(),
+                ));
+            }
+            Ok(())
+        }
+
+        Commands::Cache {
+            action: CacheAction::Clear { repo },
+        } => {
+            match AnalysisCache::clear(&repo)? {
+                Some(dir) => println!("Removed {}", dir.display()),
+                None => println!("No cache to clear"),
+            }
+            Ok(())
+        }
+
         Commands::Tui { repo } => {
             tui::run_tui(&repo)?;
             Ok(())
         }
     }
 }
+
+/// The config and the staged dependencies it wants a reason for
+fn staged_additions(repo: &Path) -> Result<(Config, Vec<Dependency>)> {
+    let config = Config::load(repo)?;
+    let orchestrator =
+        DependencyOrchestrator::new(create_default_registry()).with_config(config.clone());
+    let exempt = config.reason_exemptions();
+    let added = orchestrator
+        .staged_additions(repo)?
+        .into_iter()
+        .filter(|dep| !exempt.is_match(&dep.name))
+        .collect();
+    Ok((config, added))
+}
+
+/// The `--format` flag wins over the config's `format`, which wins over text
+fn resolve_format<F: ValueEnum>(flag: Option<F>, config: &Config) -> Result<F> {
+    if let Some(format) = flag {
+        return Ok(format);
+    }
+    let format = config.format.as_deref().unwrap_or("text");
+    F::from_str(format, true)
+        .map_err(|_| DependencyBlameError::Other(format!("Invalid format '{}' in config", format)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_flag_wins_over_config() {
+        let config = Config {
+            format: Some("json".to_string()),
+            ..Config::default()
+        };
+
+        assert!(matches!(
+            resolve_format(Some(OutputFormat::Text), &config),
+            Ok(OutputFormat::Text)
+        ));
+        assert!(matches!(
+            resolve_format::<OutputFormat>(None, &config),
+            Ok(OutputFormat::Json)
+        ));
+        assert!(matches!(
+            resolve_format::<OutputFormat>(None, &Config::default()),
+            Ok(OutputFormat::Text)
+        ));
+        let config = Config {
+            format: Some("yaml".to_string()),
+            ..Config::default()
+        };
+        assert!(resolve_format::<OutputFormat>(None, &config).is_err());
+    }
+}
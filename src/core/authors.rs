@@ -0,0 +1,78 @@
+use crate::core::dependency::GitInfo;
+use crate::core::graveyard::RemovedDependency;
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Dependency activity of one person or team
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct AuthorStats {
+    /// `Name <email>` after `.mailmap`, or the team name
+    pub author: String,
+    /// Declared dependencies they added
+    pub introduced: usize,
+    /// Of those, the ones no usage was found for
+    pub unused: usize,
+    /// Dependencies they removed
+    pub removed: usize,
+    /// Names of the declared dependencies they added
+    pub dependencies: Vec<String>,
+}
+
+/// A declared dependency with the commit that added it
+pub struct Introduced<'a> {
+    pub name: &'a str,
+    pub git_info: &'a GitInfo,
+    pub is_unused: bool,
+}
+
+/// Tally introductions and removals per author, or per whatever `group`
+/// maps a commit's author to, most introductions first
+pub fn summarize<F>(
+    introduced: &[Introduced],
+    removed: &[RemovedDependency],
+    group: F,
+) -> Vec<AuthorStats>
+where
+    F: Fn(&GitInfo) -> String,
+{
+    let mut stats: HashMap<String, AuthorStats> = HashMap::new();
+    for dependency in introduced {
+        let author = author_entry(&mut stats, group(dependency.git_info));
+        author.introduced += 1;
+        if dependency.is_unused {
+            author.unused += 1;
+        }
+        author.dependencies.push(dependency.name.to_string());
+    }
+    for dependency in removed {
+        author_entry(&mut stats, group(&dependency.removed)).removed += 1;
+    }
+
+    let mut stats: Vec<AuthorStats> = stats.into_values().collect();
+    for author in &mut stats {
+        author.dependencies.sort();
+        author.dependencies.dedup();
+    }
+    stats.sort_by(|a, b| {
+        b.introduced
+            .cmp(&a.introduced)
+            .then(b.removed.cmp(&a.removed))
+            .then_with(|| a.author.cmp(&b.author))
+    });
+    stats
+}
+
+fn author_entry(stats: &mut HashMap<String, AuthorStats>, key: String) -> &mut AuthorStats {
+    stats.entry(key.clone()).or_insert_with(|| AuthorStats {
+        author: key,
+        ..AuthorStats::default()
+    })
+}
+
+/// The email of a `Name <email>` author
+pub fn author_email(author: &str) -> &str {
+    author
+        .rsplit_once('<')
+        .and_then(|(_, rest)| rest.strip_suffix('>'))
+        .unwrap_or(author)
+}
@@ -0,0 +1,303 @@
+use crate::core::dependency::{EcosystemType, UsageKind};
+use crate::core::error::{DependencyBlameError, Result};
+use git2::{ObjectType, Oid, Repository};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Directory inside the git directory that holds the cache files
+const CACHE_DIR: &str = "dependency-blame";
+const HISTORY_FILE: &str = "history.json";
+const IMPORTS_FILE: &str = "imports.json";
+
+/// Cached results are discarded when they were written by another version,
+/// since the file format may have changed. Import results are also keyed by
+/// the version of the scanner that extracted them.
+const CACHE_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// The commits of HEAD's history in which a manifest changed, oldest first.
+///
+/// Commits in which the manifest blob is the same as in the previous entry
+/// are left out, so each entry marks the manifest taking a new content. The
+/// manifest is followed through renames, so older entries may be at another path.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ManifestIndex {
+    /// HEAD commit the index was built for
+    pub head: String,
+    /// Shallow clone boundaries at the time, so deepening rebuilds the index
+    #[serde(default)]
+    pub shallow: Vec<String>,
+    pub entries: Vec<ManifestRevision>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManifestRevision {
+    pub commit: String,
+    /// Blob of the manifest in that commit, `None` when it did not exist
+    pub blob: Option<String>,
+    /// Path of the manifest in that commit, `None` when it did not exist
+    pub path: Option<String>,
+}
+
+impl ManifestIndex {
+    /// Append a commit, unless the manifest content is unchanged
+    pub fn push(&mut self, commit: Oid, file: Option<(Oid, &Path)>) {
+        let blob = file.map(|(blob, _)| blob.to_string());
+        if self.entries.last().map(|e| &e.blob) == Some(&blob) {
+            return;
+        }
+        self.entries.push(ManifestRevision {
+            commit: commit.to_string(),
+            blob,
+            path: file.map(|(_, path)| path.to_string_lossy().into_owned()),
+        });
+    }
+
+    /// Paths the manifest had, newest first
+    pub fn paths(&self) -> Vec<PathBuf> {
+        let mut paths: Vec<PathBuf> = Vec::new();
+        for path in self.entries.iter().rev().filter_map(|e| e.path.as_ref()) {
+            let path = PathBuf::from(path);
+            if !paths.contains(&path) {
+                paths.push(path);
+            }
+        }
+        paths
+    }
+}
+
+/// Imports extracted from one segment of a file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedSegment {
+    pub line_offset: usize,
+    pub cell_index: Option<usize>,
+    pub imports: Vec<CachedImport>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedImport {
+    pub module: String,
+    /// 0-based line index within the segment
+    pub line: usize,
+    pub kind: UsageKind,
+    /// Trimmed text of the line
+    pub text: String,
+}
+
+#[derive(Deserialize)]
+struct CacheFile<T> {
+    version: String,
+    entries: HashMap<String, T>,
+}
+
+#[derive(Serialize)]
+struct CacheFileRef<'a, T> {
+    version: &'a str,
+    entries: &'a HashMap<String, T>,
+}
+
+/// Analysis results persisted in `.git/dependency-blame/` between runs.
+///
+/// Holds the manifest history index, keyed by manifest path and the HEAD it
+/// was built for, and per-file import extraction results, keyed by ecosystem
+/// and the file's blob hash. Safe to share between scanning threads.
+pub struct AnalysisCache {
+    dir: PathBuf,
+    history: Mutex<HashMap<String, ManifestIndex>>,
+    imports: Mutex<HashMap<String, Vec<CachedSegment>>>,
+    /// Import entries looked up or added during this run; others are pruned on save
+    used_imports: Mutex<HashSet<String>>,
+}
+
+impl AnalysisCache {
+    /// Open the cache of the git repository containing `repo_path`.
+    /// Returns `None` outside of a git repository.
+    pub fn open(repo_path: &Path) -> Option<Self> {
+        let dir = find_cache_dir(repo_path)?;
+        Some(Self {
+            history: Mutex::new(read_entries(&dir.join(HISTORY_FILE))),
+            imports: Mutex::new(read_entries(&dir.join(IMPORTS_FILE))),
+            used_imports: Mutex::new(HashSet::new()),
+            dir,
+        })
+    }
+
+    /// Delete the cache of the git repository containing `repo_path`.
+    /// Returns the removed directory, if there was one.
+    pub fn clear(repo_path: &Path) -> Result<Option<PathBuf>> {
+        let dir = find_cache_dir(repo_path)
+            .ok_or_else(|| DependencyBlameError::GitRepoNotFound(repo_path.to_path_buf()))?;
+        if !dir.exists() {
+            return Ok(None);
+        }
+        fs::remove_dir_all(&dir)?;
+        Ok(Some(dir))
+    }
+
+    pub fn manifest_index(&self, manifest: &Path) -> Option<ManifestIndex> {
+        self.history
+            .lock()
+            .ok()?
+            .get(&manifest.to_string_lossy().into_owned())
+            .cloned()
+    }
+
+    pub fn set_manifest_index(&self, manifest: &Path, index: ManifestIndex) {
+        if let Ok(mut history) = self.history.lock() {
+            history.insert(manifest.to_string_lossy().into_owned(), index);
+        }
+    }
+
+    pub fn imports(
+        &self,
+        ecosystem: EcosystemType,
+        scanner_version: u32,
+        file_path: &Path,
+        content: &[u8],
+    ) -> Option<Vec<CachedSegment>> {
+        let key = import_key(ecosystem, scanner_version, file_path, content)?;
+        let found = self.imports.lock().ok()?.get(&key).cloned();
+        if found.is_some() {
+            if let Ok(mut used) = self.used_imports.lock() {
+                used.insert(key);
+            }
+        }
+        found
+    }
+
+    pub fn set_imports(
+        &self,
+        ecosystem: EcosystemType,
+        scanner_version: u32,
+        file_path: &Path,
+        content: &[u8],
+        segments: Vec<CachedSegment>,
+    ) {
+        let key = match import_key(ecosystem, scanner_version, file_path, content) {
+            Some(key) => key,
+            None => return,
+        };
+        if let (Ok(mut imports), Ok(mut used)) = (self.imports.lock(), self.used_imports.lock()) {
+            imports.insert(key.clone(), segments);
+            used.insert(key);
+        }
+    }
+
+    /// Write the cache back to disk. Import results for files of the scanned
+    /// ecosystems that were not seen during this run are dropped.
+    pub fn save(&self) -> Result<()> {
+        fs::create_dir_all(&self.dir)?;
+
+        if let Ok(history) = self.history.lock() {
+            write_entries(&self.dir.join(HISTORY_FILE), &history)?;
+        }
+
+        if let (Ok(mut imports), Ok(used)) = (self.imports.lock(), self.used_imports.lock()) {
+            let scanned: HashSet<&str> = used
+                .iter()
+                .filter_map(|key| key.split_once(':').map(|(ecosystem, _)| ecosystem))
+                .collect();
+            imports.retain(|key, _| {
+                let ecosystem = key.split_once(':').map(|(e, _)| e).unwrap_or("");
+                !scanned.contains(&ecosystem) || used.contains(key)
+            });
+            write_entries(&self.dir.join(IMPORTS_FILE), &imports)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The cache directory of the repository containing `repo_path`
+fn find_cache_dir(repo_path: &Path) -> Option<PathBuf> {
+    let repo = Repository::discover(repo_path).ok()?;
+    Some(cache_dir(&repo))
+}
+
+/// A `filter_entry` predicate for walks of `repo_path` that skips the cache
+/// directory of its repository. Walks that include hidden files enter
+/// `.git`, and in a bare repository the git directory is the one walked.
+pub fn skip_cache_dir(repo_path: &Path) -> impl Fn(&ignore::DirEntry) -> bool + Send + Sync {
+    let cache_dir = find_cache_dir(repo_path).and_then(|dir| dir.canonicalize().ok());
+    move |entry| {
+        entry.file_name() != CACHE_DIR
+            || cache_dir.is_none()
+            || entry.path().canonicalize().ok() != cache_dir
+    }
+}
+
+/// The cache directory inside a repository's git directory
+pub(crate) fn cache_dir(repo: &Repository) -> PathBuf {
+    repo.path().join(CACHE_DIR)
+}
+
+/// Files are keyed by the same hash git would give them, by the version of
+/// the scanner, and by extension since that decides which parts of a file
+/// are scanned (a `.vue` file's `<script>` blocks, a `.ts` file as a whole)
+fn import_key(
+    ecosystem: EcosystemType,
+    scanner_version: u32,
+    file_path: &Path,
+    content: &[u8],
+) -> Option<String> {
+    let oid = Oid::hash_object(ObjectType::Blob, content).ok()?;
+    let extension = file_path
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or_default();
+    Some(format!(
+        "{}:{}:{}:{}",
+        ecosystem.as_str(),
+        scanner_version,
+        extension,
+        oid
+    ))
+}
+
+/// Read a cache file, treating a missing, unreadable or outdated one as empty
+fn read_entries<T: DeserializeOwned>(path: &Path) -> HashMap<String, T> {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|content| serde_json::from_str::<CacheFile<T>>(&content).ok())
+        .filter(|file| file.version == CACHE_VERSION)
+        .map(|file| file.entries)
+        .unwrap_or_default()
+}
+
+fn write_entries<T: Serialize>(path: &Path, entries: &HashMap<String, T>) -> Result<()> {
+    let file = CacheFileRef {
+        version: CACHE_VERSION,
+        entries,
+    };
+    fs::write(path, serde_json::to_string(&file)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn import_key_depends_on_extension() {
+        let content = b"<script>import x from 'x'</script>";
+        let vue = import_key(EcosystemType::Node, 1, Path::new("a.vue"), content);
+        let ts = import_key(EcosystemType::Node, 1, Path::new("b.ts"), content);
+        assert_ne!(vue, ts);
+        assert_eq!(
+            vue,
+            import_key(EcosystemType::Node, 1, Path::new("src/c.vue"), content)
+        );
+    }
+
+    #[test]
+    fn import_key_depends_on_scanner_version() {
+        let content = b"import x from 'x'";
+        assert_ne!(
+            import_key(EcosystemType::Node, 1, Path::new("a.ts"), content),
+            import_key(EcosystemType::Node, 2, Path::new("a.ts"), content)
+        );
+    }
+}
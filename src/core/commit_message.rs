@@ -0,0 +1,352 @@
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+
+/// Type and scope of a Conventional Commits subject, e.g. `feat(api)!: ...`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConventionalCommit {
+    /// `feat`, `fix`, `chore`, ... in lowercase
+    pub kind: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
+    /// Marked with `!` or a `BREAKING CHANGE` trailer
+    pub breaking: bool,
+}
+
+/// An issue or ticket a commit message refers to
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IssueReference {
+    /// The reference as written, e.g. `#123` or `PROJ-456`; `owner/repo#123`
+    /// for issue and pull request URLs
+    pub key: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+}
+
+/// A `Key: value` trailer from the last paragraph of a commit message
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Trailer {
+    pub key: String,
+    pub value: String,
+}
+
+/// How issue references are recognized in commit messages
+#[derive(Debug, Clone, Default)]
+pub struct IssuePatterns {
+    /// Ticket keys such as `PROJ-\d+`, in addition to `#123`, `GH-123` and URLs
+    keys: Vec<Regex>,
+    /// Link for keys matched by `keys`, with `{key}` standing for the key
+    url: Option<String>,
+}
+
+impl IssuePatterns {
+    /// Patterns that fail to compile are skipped
+    pub fn new<S: AsRef<str>>(keys: &[S], url: Option<String>) -> Self {
+        Self {
+            keys: keys
+                .iter()
+                .filter_map(|key| Regex::new(&format!(r"\b(?:{})\b", key.as_ref())).ok())
+                .collect(),
+            url,
+        }
+    }
+}
+
+/// Parse a Conventional Commits subject line
+pub fn conventional_commit(message: &str) -> Option<ConventionalCommit> {
+    static SUBJECT: OnceLock<Regex> = OnceLock::new();
+    let subject_pattern = SUBJECT
+        .get_or_init(|| Regex::new(r"^([A-Za-z]+)(?:\(([^)]+)\))?(!)?: \S").expect("valid regex"));
+    let subject = message.lines().next().unwrap_or("").trim();
+    let captures = subject_pattern.captures(subject)?;
+    let breaking_trailer = trailers(message)
+        .iter()
+        .any(|t| t.key == "BREAKING CHANGE" || t.key == "BREAKING-CHANGE");
+
+    Some(ConventionalCommit {
+        kind: captures[1].to_lowercase(),
+        scope: captures
+            .get(2)
+            .map(|scope| scope.as_str().trim().to_string()),
+        breaking: captures.get(3).is_some() || breaking_trailer,
+    })
+}
+
+/// Issue references in a commit message, in order of appearance and without
+/// duplicates: tracker URLs, `#123`, `GH-123` and the configured ticket keys
+pub fn issue_references(message: &str, patterns: &IssuePatterns) -> Vec<IssueReference> {
+    static URL: OnceLock<Regex> = OnceLock::new();
+    static NUMBER: OnceLock<Regex> = OnceLock::new();
+    let url_pattern = URL.get_or_init(|| {
+        Regex::new(
+            r"https?://[^\s/]+/(\S+?)/(?:-/)?(issues|pull|pulls|merge_requests|browse|tickets?)/([\w-]+)",
+        )
+        .expect("valid regex")
+    });
+    let number_pattern =
+        NUMBER.get_or_init(|| Regex::new(r"(?:^|[\s(\[])((?:#|GH-)\d+)\b").expect("valid regex"));
+
+    let mut found: Vec<(usize, IssueReference)> = Vec::new();
+    let mut covered: Vec<(usize, usize)> = Vec::new();
+
+    for captures in url_pattern.captures_iter(message) {
+        let url = captures.get(0).expect("group 0 always matches");
+        let (project, kind, id) = (&captures[1], &captures[2], &captures[3]);
+        // owner/repo#17, group/project!5 or the ticket key; other URLs as they are
+        let key = match kind {
+            "issues" | "pull" | "pulls" => format!("{}#{}", project, id),
+            "merge_requests" => format!("{}!{}", project, id),
+            "browse" => id.to_string(),
+            _ => url.as_str().to_string(),
+        };
+        covered.push((url.start(), url.end()));
+        found.push((
+            url.start(),
+            IssueReference {
+                key,
+                url: Some(url.as_str().to_string()),
+            },
+        ));
+    }
+
+    // References inside a URL were already reported with it
+    let is_covered = |start: usize| covered.iter().any(|(s, e)| (*s..*e).contains(&start));
+
+    for captures in number_pattern.captures_iter(message) {
+        let reference = captures.get(1).expect("group 1 always matches");
+        if !is_covered(reference.start()) {
+            found.push((
+                reference.start(),
+                IssueReference {
+                    key: reference.as_str().to_string(),
+                    url: None,
+                },
+            ));
+        }
+    }
+
+    for pattern in &patterns.keys {
+        for key in pattern.find_iter(message) {
+            if !is_covered(key.start()) {
+                found.push((
+                    key.start(),
+                    IssueReference {
+                        key: key.as_str().to_string(),
+                        url: patterns
+                            .url
+                            .as_ref()
+                            .map(|url| url.replace("{key}", key.as_str())),
+                    },
+                ));
+            }
+        }
+    }
+
+    found.sort_by_key(|(start, _)| *start);
+    let mut references: Vec<IssueReference> = Vec::new();
+    for (_, reference) in found {
+        if !references.iter().any(|r| r.key == reference.key) {
+            references.push(reference);
+        }
+    }
+    references
+}
+
+/// Trailers such as `Co-authored-by` and `Reviewed-by` from the last
+/// paragraph of the message, when every line of it is a trailer
+pub fn trailers(message: &str) -> Vec<Trailer> {
+    static TRAILER: OnceLock<Regex> = OnceLock::new();
+    let trailer_pattern = TRAILER.get_or_init(|| {
+        Regex::new(r"^([A-Za-z][A-Za-z0-9-]*|BREAKING CHANGE): (.+)$").expect("valid regex")
+    });
+    let paragraphs: Vec<&str> = message
+        .trim()
+        .split("\n\n")
+        .map(str::trim)
+        .filter(|p| !p.is_empty())
+        .collect();
+
+    // The subject is never a trailer paragraph
+    let last = match paragraphs.as_slice() {
+        [_, .., last] => last,
+        _ => return Vec::new(),
+    };
+
+    let mut trailers = Vec::new();
+    for line in last.lines() {
+        match trailer_pattern.captures(line.trim()) {
+            Some(captures) => trailers.push(Trailer {
+                key: captures[1].to_string(),
+                value: captures[2].trim().to_string(),
+            }),
+            None => return Vec::new(),
+        }
+    }
+    trailers
+}
+
+/// Whether the message names the dependency, as a word of its own
+pub fn mentions(message: &str, dependency_name: &str) -> bool {
+    mention_pattern(dependency_name).is_some_and(|mention| mention.is_match(message))
+}
+
+/// Compiled once per lookup; `reason` matches it against every sentence
+fn mention_pattern(dependency_name: &str) -> Option<Regex> {
+    Regex::new(&format!(
+        r"(?i)(?:^|[^\w@./-]){}(?:$|[^\w./-])",
+        regex::escape(dependency_name)
+    ))
+    .ok()
+}
+
+/// Best-effort sentence explaining why a dependency was touched: the first
+/// sentence mentioning it that gives a reason ("for", "because", "to", ...),
+/// or else the first sentence of the body mentioning it
+pub fn reason(message: &str, dependency_name: &str) -> Option<String> {
+    let mention = mention_pattern(dependency_name)?;
+    static CAUSAL: OnceLock<Regex> = OnceLock::new();
+    let causal = CAUSAL.get_or_init(|| {
+        Regex::new(
+            r"(?i)\b(?:because|since|for|to|so|needed?|needs|requires?|instead|replac\w*|due)\b",
+        )
+        .expect("valid regex")
+    });
+    let has_trailers = !trailers(message).is_empty();
+    let mut paragraphs: Vec<&str> = message
+        .trim()
+        .split("\n\n")
+        .filter(|p| !p.trim().is_empty())
+        .collect();
+    if has_trailers {
+        paragraphs.pop();
+    }
+
+    // Join wrapped lines back into sentences; list items stand on their own
+    let mut units: Vec<(bool, String)> = Vec::new();
+    for (index, paragraph) in paragraphs.iter().enumerate() {
+        for (line_index, line) in paragraph.lines().enumerate() {
+            let line = line.trim();
+            let is_subject = index == 0 && line_index == 0;
+            let is_item = line.starts_with(['-', '*']);
+            let text = line.trim_start_matches(['-', '*']).trim();
+            match units.last_mut() {
+                Some((false, unit)) if !is_item && line_index > 0 => {
+                    unit.push(' ');
+                    unit.push_str(text);
+                }
+                _ => units.push((is_subject, text.to_string())),
+            }
+        }
+    }
+
+    let mut sentences: Vec<(bool, String)> = Vec::new();
+    for (is_subject, unit) in units {
+        for sentence in unit.split_inclusive(". ") {
+            let sentence = sentence.trim();
+            if !sentence.is_empty() {
+                sentences.push((is_subject, sentence.to_string()));
+            }
+        }
+    }
+
+    let mentioning = || sentences.iter().filter(|(_, s)| mention.is_match(s));
+    mentioning()
+        .find(|(_, s)| causal.is_match(s))
+        .or_else(|| mentioning().find(|(is_subject, _)| !is_subject))
+        .map(|(_, s)| s.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trailer(key: &str, value: &str) -> Trailer {
+        Trailer {
+            key: key.to_string(),
+            value: value.to_string(),
+        }
+    }
+
+    #[test]
+    fn conventional_subject() {
+        assert_eq!(
+            conventional_commit("feat(api)!: drop v1 endpoints"),
+            Some(ConventionalCommit {
+                kind: "feat".to_string(),
+                scope: Some("api".to_string()),
+                breaking: true,
+            })
+        );
+        assert_eq!(
+            conventional_commit("Fix: typo"),
+            Some(ConventionalCommit {
+                kind: "fix".to_string(),
+                scope: None,
+                breaking: false,
+            })
+        );
+    }
+
+    #[test]
+    fn breaking_change_trailer() {
+        let commit =
+            conventional_commit("chore: bump tokio\n\nBREAKING CHANGE: needs Rust 1.70").unwrap();
+        assert!(commit.breaking);
+    }
+
+    #[test]
+    fn not_conventional() {
+        assert_eq!(conventional_commit("Add serde"), None);
+        assert_eq!(conventional_commit("feat:no space"), None);
+        assert_eq!(conventional_commit("Merge branch 'a': b"), None);
+    }
+
+    #[test]
+    fn trailers_of_the_last_paragraph() {
+        let message = "Add serde\n\nWe need it.\n\nCo-authored-by: Ann <ann@x>\nReviewed-by: Bo";
+        assert_eq!(
+            trailers(message),
+            [
+                trailer("Co-authored-by", "Ann <ann@x>"),
+                trailer("Reviewed-by", "Bo")
+            ]
+        );
+    }
+
+    #[test]
+    fn paragraph_with_prose_has_no_trailers() {
+        assert!(trailers("Add serde\n\nNote: this is prose\nthat wraps").is_empty());
+        // The subject alone is never a trailer
+        assert!(trailers("Fixes: #12").is_empty());
+    }
+
+    #[test]
+    fn issue_references_in_order_without_duplicates() {
+        let patterns = IssuePatterns::new(&["PROJ-\\d+"], Some("https://t/{key}".to_string()));
+        let references = issue_references(
+            "Fix PROJ-4 (#12)\n\nSee https://github.com/o/r/issues/9, #12 and GH-3",
+            &patterns,
+        );
+        let keys: Vec<&str> = references.iter().map(|r| r.key.as_str()).collect();
+        assert_eq!(keys, ["PROJ-4", "#12", "o/r#9", "GH-3"]);
+        assert_eq!(references[0].url.as_deref(), Some("https://t/PROJ-4"));
+    }
+
+    #[test]
+    fn reason_prefers_a_causal_sentence() {
+        let message =
+            "Add serde\n\nThis adds serde. We need serde because the config moved\nto TOML.";
+        assert_eq!(
+            reason(message, "serde").as_deref(),
+            Some("We need serde because the config moved to TOML.")
+        );
+        assert_eq!(reason(message, "serde_json"), None);
+    }
+
+    #[test]
+    fn mentions_whole_names_only() {
+        assert!(mentions("Use Serde for config", "serde"));
+        assert!(!mentions("Use serde_json", "serde"));
+        assert!(!mentions("Add @types/react", "react"));
+    }
+}
@@ -0,0 +1,380 @@
+use crate::core::commit_message::IssuePatterns;
+use crate::core::dependency::{Dependency, EcosystemType};
+use crate::core::error::{DependencyBlameError, Result};
+use crate::utils::patterns::NameGlobs;
+use git2::Repository;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Name of the project configuration file, looked up from the repository
+/// directory upwards to the root of the git repository
+pub const CONFIG_FILE_NAME: &str = ".dependency-blame.toml";
+
+/// Paths treated as tests in every project, on top of configured `test_paths`
+pub const DEFAULT_TEST_PATHS: &[&str] = &[
+    "test",
+    "tests",
+    "__tests__",
+    "spec",
+    "testdata",
+    "*_test.go",
+    "*_test.py",
+    "test_*.py",
+    "*.test.*",
+    "*.spec.*",
+];
+
+/// Settings from `.dependency-blame.toml` and the user-level config file.
+///
+/// Precedence, from highest to lowest: command-line flags, the project
+/// config, the user config, built-in defaults. When both files set the same
+/// value the project wins; lists are concatenated and tables are merged
+/// key by key.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Default output format (`text` or `json`)
+    pub format: Option<String>,
+    pub scan: ScanConfig,
+    /// Import names of dependencies that are imported under another name,
+    /// e.g. `beautifulsoup4 = ["bs4"]`
+    pub imports: BTreeMap<String, Vec<String>>,
+    /// Dependencies that are known to look unused, with the reason
+    pub ignore: BTreeMap<String, String>,
+    /// Per-ecosystem settings, keyed by `rust`, `node`, `python`, `go` or `deno`
+    pub ecosystems: BTreeMap<String, EcosystemConfig>,
+    pub issues: IssueConfig,
+    pub releases: ReleaseConfig,
+    /// Teams and the author email globs of their members,
+    /// e.g. `platform = ["*@platform.example.com"]`
+    pub teams: BTreeMap<String, Vec<String>>,
+    pub hooks: HookConfig,
+}
+
+/// Where usage scanning looks for imports
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ScanConfig {
+    /// Directories scanned in addition to the manifest's, relative to the repository
+    pub paths: Vec<PathBuf>,
+    /// Globs of files and directories to skip, relative to the repository
+    pub exclude: Vec<String>,
+    /// Globs of test files and directories, in addition to [`DEFAULT_TEST_PATHS`]
+    pub test_paths: Vec<String>,
+}
+
+/// How issue references are found in commit messages
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct IssueConfig {
+    /// Regexes of ticket keys, e.g. `PROJ-\\d+`; `#123`, `GH-123` and tracker
+    /// URLs are always recognized
+    pub patterns: Vec<String>,
+    /// Link for matched keys, with `{key}` standing for the key,
+    /// e.g. `https://example.atlassian.net/browse/{key}`
+    pub url: Option<String>,
+}
+
+/// Which tags mark releases
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ReleaseConfig {
+    /// Glob of release tag names, e.g. `v*`; all tags when unset
+    pub tags: Option<String>,
+}
+
+/// What the commit hooks of `install-hooks` require
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct HookConfig {
+    /// Set to false to only warn about new dependencies without a reason
+    pub enforce: Option<bool>,
+    /// Globs of dependency names that need no reason, e.g. `@types/*`
+    pub allow: Vec<String>,
+}
+
+/// Settings that only apply to one ecosystem
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct EcosystemConfig {
+    /// Set to false to skip the ecosystem's manifests during detection
+    pub enabled: Option<bool>,
+    pub scan: ScanConfig,
+    pub imports: BTreeMap<String, Vec<String>>,
+}
+
+impl Config {
+    /// Load the user config and the project config for `repo_path`, merged
+    pub fn load(repo_path: &Path) -> Result<Self> {
+        Self::load_with(user_config_path().as_deref(), repo_path)
+    }
+
+    fn load_with(user_config: Option<&Path>, repo_path: &Path) -> Result<Self> {
+        let mut config = match user_config {
+            Some(path) if path.is_file() => Self::from_file(path)?,
+            _ => Self::default(),
+        };
+        if let Some(path) = Self::discover(repo_path) {
+            config = config.merge(Self::from_file(&path)?);
+        }
+        Ok(config)
+    }
+
+    /// Find the project config file in `repo_path` or the closest parent
+    /// directory up to the root of its git repository. Outside of a
+    /// repository only `repo_path` itself is looked at.
+    pub fn discover(repo_path: &Path) -> Option<PathBuf> {
+        let canonical = |path: &Path| path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        let start = canonical(repo_path);
+        let root = Repository::discover(&start)
+            .ok()
+            .and_then(|repo| repo.workdir().map(canonical));
+
+        for dir in start.ancestors() {
+            let path = dir.join(CONFIG_FILE_NAME);
+            if path.is_file() {
+                return Some(path);
+            }
+            if root.as_deref().is_none_or(|root| dir == root) {
+                break;
+            }
+        }
+        None
+    }
+
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)?;
+        toml::from_str(&content).map_err(|e| DependencyBlameError::ConfigError {
+            file: path.display().to_string(),
+            reason: e.to_string(),
+        })
+    }
+
+    /// Layer `other` on top of this config
+    pub fn merge(mut self, other: Config) -> Self {
+        self.format = other.format.or(self.format);
+        self.scan = self.scan.merge(other.scan);
+        merge_lists(&mut self.imports, other.imports);
+        self.ignore.extend(other.ignore);
+        self.issues.patterns.extend(other.issues.patterns);
+        self.issues.url = other.issues.url.or(self.issues.url);
+        self.releases.tags = other.releases.tags.or(self.releases.tags);
+        merge_lists(&mut self.teams, other.teams);
+        self.hooks.enforce = other.hooks.enforce.or(self.hooks.enforce);
+        self.hooks.allow.extend(other.hooks.allow);
+        for (key, theirs) in other.ecosystems {
+            let merged = match self.ecosystems.remove(&key) {
+                Some(ours) => ours.merge(theirs),
+                None => theirs,
+            };
+            self.ecosystems.insert(key, merged);
+        }
+        self
+    }
+
+    /// Settings for one ecosystem, if configured
+    pub fn ecosystem(&self, ecosystem: EcosystemType) -> Option<&EcosystemConfig> {
+        self.ecosystems.get(ecosystem_key(ecosystem))
+    }
+
+    pub fn is_ecosystem_enabled(&self, ecosystem: EcosystemType) -> bool {
+        self.ecosystem(ecosystem)
+            .and_then(|e| e.enabled)
+            .unwrap_or(true)
+    }
+
+    /// Scan settings for an ecosystem: the global ones plus the ecosystem's own
+    pub fn scan_for(&self, ecosystem: EcosystemType) -> ScanConfig {
+        match self.ecosystem(ecosystem) {
+            Some(config) => self.scan.clone().merge(config.scan.clone()),
+            None => self.scan.clone(),
+        }
+    }
+
+    /// Extra names the dependency may be imported as
+    pub fn import_names(&self, dependency: &Dependency) -> Vec<String> {
+        let ecosystem_names = self
+            .ecosystem(dependency.ecosystem)
+            .and_then(|e| e.imports.get(&dependency.name));
+        self.imports
+            .get(&dependency.name)
+            .into_iter()
+            .chain(ecosystem_names)
+            .flatten()
+            .cloned()
+            .collect()
+    }
+
+    /// Compiled issue key patterns
+    pub fn issue_patterns(&self) -> IssuePatterns {
+        IssuePatterns::new(&self.issues.patterns, self.issues.url.clone())
+    }
+
+    /// Compiled `teams` globs
+    pub fn team_patterns(&self) -> TeamPatterns {
+        TeamPatterns {
+            teams: self
+                .teams
+                .iter()
+                .map(|(team, globs)| {
+                    let globs: Vec<String> = globs.iter().map(|g| g.to_lowercase()).collect();
+                    (team.clone(), NameGlobs::new(&globs))
+                })
+                .collect(),
+        }
+    }
+
+    /// Compiled `hooks.allow` globs, matching the dependencies the commit
+    /// hooks want no reason for
+    pub fn reason_exemptions(&self) -> NameGlobs {
+        NameGlobs::new(&self.hooks.allow)
+    }
+
+    /// Why the dependency is ignored, if it is
+    pub fn ignore_reason(&self, dependency_name: &str) -> Option<&str> {
+        self.ignore.get(dependency_name).map(String::as_str)
+    }
+}
+
+/// Author email globs by team, see [`Config::team_patterns`]
+#[derive(Debug, Clone)]
+pub struct TeamPatterns {
+    teams: Vec<(String, NameGlobs)>,
+}
+
+impl TeamPatterns {
+    /// The team of an author email, from the first team with a matching glob
+    pub fn team_for(&self, email: &str) -> Option<&str> {
+        let email = email.to_lowercase();
+        self.teams
+            .iter()
+            .find(|(_, globs)| globs.is_match(&email))
+            .map(|(team, _)| team.as_str())
+    }
+}
+
+impl ScanConfig {
+    fn merge(mut self, other: ScanConfig) -> Self {
+        self.paths.extend(other.paths);
+        self.exclude.extend(other.exclude);
+        self.test_paths.extend(other.test_paths);
+        self
+    }
+
+    /// Configured test globs together with [`DEFAULT_TEST_PATHS`]
+    pub fn all_test_paths(&self) -> Vec<String> {
+        DEFAULT_TEST_PATHS
+            .iter()
+            .map(|p| p.to_string())
+            .chain(self.test_paths.iter().cloned())
+            .collect()
+    }
+}
+
+impl EcosystemConfig {
+    fn merge(mut self, other: EcosystemConfig) -> Self {
+        self.enabled = other.enabled.or(self.enabled);
+        self.scan = self.scan.merge(other.scan);
+        merge_lists(&mut self.imports, other.imports);
+        self
+    }
+}
+
+/// Merge tables of lists key by key, without duplicating entries
+fn merge_lists(ours: &mut BTreeMap<String, Vec<String>>, theirs: BTreeMap<String, Vec<String>>) {
+    for (key, values) in theirs {
+        let entry = ours.entry(key).or_default();
+        for value in values {
+            if !entry.contains(&value) {
+                entry.push(value);
+            }
+        }
+    }
+}
+
+/// `$XDG_CONFIG_HOME/dependency-blame/config.toml`, falling back to
+/// `~/.config/dependency-blame/config.toml`
+pub fn user_config_path() -> Option<PathBuf> {
+    let config_home = env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(config_home.join("dependency-blame").join("config.toml"))
+}
+
+/// The name of an ecosystem in config tables, such as `[ecosystems.node]`
+pub(crate) fn ecosystem_key(ecosystem: EcosystemType) -> &'static str {
+    match ecosystem {
+        EcosystemType::Rust => "rust",
+        EcosystemType::Node => "node",
+        EcosystemType::Python => "python",
+        EcosystemType::Go => "go",
+        EcosystemType::Deno => "deno",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn project_config_wins_over_user_config() {
+        let dir = tempfile::tempdir().unwrap();
+        Repository::init(dir.path()).unwrap();
+        let user_config = dir.path().join("user.toml");
+        fs::write(
+            &user_config,
+            "format = \"json\"\nignore = { log = \"Used by macros\" }\n\n[releases]\ntags = \"v*\"\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join(CONFIG_FILE_NAME),
+            "format = \"text\"\n\n[ignore]\nserde = \"Derives only\"\n",
+        )
+        .unwrap();
+
+        let config = Config::load_with(Some(&user_config), dir.path()).unwrap();
+        assert_eq!(config.format.as_deref(), Some("text"));
+        assert_eq!(config.releases.tags.as_deref(), Some("v*"));
+        assert_eq!(config.ignore_reason("log"), Some("Used by macros"));
+        assert_eq!(config.ignore_reason("serde"), Some("Derives only"));
+
+        let defaults = Config::load_with(None, &dir.path().join("missing")).unwrap();
+        assert_eq!(defaults.format, None);
+        assert!(defaults.is_ecosystem_enabled(EcosystemType::Go));
+    }
+
+    #[test]
+    fn discovery_stops_at_the_repository_root() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join(CONFIG_FILE_NAME), "format = \"json\"\n").unwrap();
+        let repo_dir = dir.path().join("repo");
+        let nested = repo_dir.join("crates/core");
+        fs::create_dir_all(&nested).unwrap();
+        Repository::init(&repo_dir).unwrap();
+
+        assert_eq!(Config::discover(&nested), None);
+
+        fs::write(repo_dir.join(CONFIG_FILE_NAME), "").unwrap();
+        assert_eq!(
+            Config::discover(&nested),
+            Some(repo_dir.canonicalize().unwrap().join(CONFIG_FILE_NAME))
+        );
+    }
+
+    #[test]
+    fn unknown_fields_are_errors() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(CONFIG_FILE_NAME);
+
+        fs::write(&path, "[scan]\nexcludes = [\"vendor\"]\n").unwrap();
+        let error = Config::from_file(&path).unwrap_err().to_string();
+        assert!(error.contains("excludes"), "{}", error);
+
+        fs::write(&path, "[ecosystems.node]\nenable = false\n").unwrap();
+        assert!(Config::from_file(&path).is_err());
+    }
+}
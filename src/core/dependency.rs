@@ -1,3 +1,7 @@
+use crate::core::commit_message::{self, ConventionalCommit, IssueReference, Trailer};
+use crate::core::graveyard::RemovedDependency;
+use crate::core::merge::PullRequest;
+use crate::core::rationale::Rationale;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
@@ -9,6 +13,46 @@ pub struct Dependency {
     pub version: String,
     pub ecosystem: EcosystemType,
     pub dependency_type: DependencyType,
+    /// Workspace package that declares the dependency, in a monorepo
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub workspace: Option<String>,
+    /// True for links to another package in the same repository (e.g. `workspace:*`)
+    #[serde(default)]
+    pub is_internal: bool,
+    /// Real package installed under this name (e.g. `npm:lodash@^4`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alias_for: Option<String>,
+    /// True when the dependency is shipped inside the published package
+    #[serde(default)]
+    pub is_bundled: bool,
+    /// Manifest declaring the dependency, relative to the repository root
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub manifest: Option<PathBuf>,
+    /// Why the dependency may look unused, from the `ignore` table of the config
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ignore_reason: Option<String>,
+}
+
+impl Dependency {
+    pub fn new(
+        name: String,
+        version: String,
+        ecosystem: EcosystemType,
+        dependency_type: DependencyType,
+    ) -> Self {
+        Self {
+            name,
+            version,
+            ecosystem,
+            dependency_type,
+            workspace: None,
+            is_internal: false,
+            alias_for: None,
+            is_bundled: false,
+            manifest: None,
+            ignore_reason: None,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
@@ -17,6 +61,7 @@ pub enum EcosystemType {
     Node,
     Python,
     Go,
+    Deno,
 }
 
 impl EcosystemType {
@@ -26,6 +71,7 @@ impl EcosystemType {
             EcosystemType::Node => "Node.js",
             EcosystemType::Python => "Python",
             EcosystemType::Go => "Go",
+            EcosystemType::Deno => "Deno",
         }
     }
 }
@@ -36,7 +82,10 @@ pub enum DependencyType {
     Dev,
     Optional,
     Peer,
+    OptionalPeer,
     Build,
+    /// Not declared directly, but its version is forced by an override
+    Override,
 }
 
 impl DependencyType {
@@ -46,7 +95,9 @@ impl DependencyType {
             DependencyType::Dev => "Development",
             DependencyType::Optional => "Optional",
             DependencyType::Peer => "Peer",
+            DependencyType::OptionalPeer => "Optional peer",
             DependencyType::Build => "Build",
+            DependencyType::Override => "Override",
         }
     }
 }
@@ -60,6 +111,132 @@ pub struct GitInfo {
     pub message: String,
     pub file_path: PathBuf,
     pub line_number: Option<usize>,
+    /// Merge commit that brought this commit into the mainline
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub merged_in: Option<Box<GitInfo>>,
+    /// Pull request parsed from the merge commit's message, or from this
+    /// commit's own message for squash merges
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pull_request: Option<PullRequest>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub conventional: Option<ConventionalCommit>,
+    /// Issues and tickets the message refers to
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub issues: Vec<IssueReference>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub trailers: Vec<Trailer>,
+    /// Sentence of the message that explains the change to the dependency
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    /// The commit is where a shallow clone's history ends, so the change was
+    /// made in it or in an earlier commit that was not fetched
+    #[serde(default)]
+    pub at_boundary: bool,
+}
+
+impl GitInfo {
+    /// Fill in `reason` from the message, for the given dependency
+    pub fn explain(&mut self, dependency_name: &str) {
+        self.reason = commit_message::reason(&self.message, dependency_name);
+    }
+}
+
+/// Line-level blame of a dependency's entry in its manifest
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EntryBlame {
+    /// First line of the entry in the current manifest (1-based)
+    pub start_line: usize,
+    /// Last line of the entry, the same as `start_line` for one-line entries
+    pub end_line: usize,
+    /// Commit that last changed any line of the entry
+    pub last_changed: GitInfo,
+    /// Commit that introduced the entry's first line, followed back through
+    /// version bumps and moves
+    pub introduced: GitInfo,
+}
+
+/// Where an uncommitted change is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LocalState {
+    /// In the index, not committed yet
+    Staged,
+    /// Only in the working tree
+    Unstaged,
+}
+
+impl LocalState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LocalState::Staged => "staged",
+            LocalState::Unstaged => "not staged",
+        }
+    }
+}
+
+/// A dependency added in the working copy and not committed yet
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LocalChange {
+    pub state: LocalState,
+    /// Identity from the git config (`user.name` and `user.email`) that
+    /// would commit it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+}
+
+/// The history available in a shallow clone
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShallowHistory {
+    /// Commits at which the fetched history ends
+    pub boundaries: Vec<String>,
+    /// Number of commits available from the analyzed commit
+    pub depth: usize,
+}
+
+/// A release tag and the commit it points to
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Release {
+    pub tag: String,
+    pub commit_hash: String,
+    pub date: DateTime<Utc>,
+}
+
+/// The releases that shipped a dependency
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReleaseRange {
+    /// Earliest release containing the commit that added the dependency and
+    /// not the one that removed it. `None` when it is not released yet, or
+    /// for a removed dependency, when no release ever shipped it.
+    pub first: Option<Release>,
+    /// Earliest release after `first` containing the commit that removed
+    /// it, the first one without it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub first_without: Option<Release>,
+}
+
+/// How a dependency was found to be used
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum UsageKind {
+    /// A runtime import (`import`, `require`, `use`, ...)
+    #[default]
+    Import,
+    /// A type-only import that does not need the package at runtime
+    TypeOnly,
+    /// Run as a CLI from a package manager script
+    Script,
+    /// Referenced from a project configuration file
+    Config,
+}
+
+impl UsageKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            UsageKind::Import => "Import",
+            UsageKind::TypeOnly => "Type-only import",
+            UsageKind::Script => "Script command",
+            UsageKind::Config => "Config reference",
+        }
+    }
 }
 
 /// Usage information from codebase scanning
@@ -68,6 +245,12 @@ pub struct UsageInfo {
     pub is_used: bool,
     pub import_locations: Vec<ImportLocation>,
     pub usage_count: usize,
+    /// Number of locations that only import types
+    #[serde(default)]
+    pub type_only_count: usize,
+    /// Number of locations in test code
+    #[serde(default)]
+    pub test_count: usize,
 }
 
 impl Default for UsageInfo {
@@ -82,18 +265,37 @@ impl UsageInfo {
             is_used: false,
             import_locations: Vec::new(),
             usage_count: 0,
+            type_only_count: 0,
+            test_count: 0,
         }
     }
 
     pub fn with_locations(locations: Vec<ImportLocation>) -> Self {
         let usage_count = locations.len();
         let is_used = usage_count > 0;
+        let type_only_count = locations
+            .iter()
+            .filter(|l| l.kind == UsageKind::TypeOnly)
+            .count();
+        let test_count = locations.iter().filter(|l| l.is_test).count();
         Self {
             is_used,
             import_locations: locations,
             usage_count,
+            type_only_count,
+            test_count,
         }
     }
+
+    /// True when every location found only imports types
+    pub fn is_type_only(&self) -> bool {
+        self.is_used && self.type_only_count == self.usage_count
+    }
+
+    /// True when every location found is in test code
+    pub fn is_test_only(&self) -> bool {
+        self.is_used && self.test_count == self.usage_count
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -101,6 +303,41 @@ pub struct ImportLocation {
     pub file_path: PathBuf,
     pub line_number: usize,
     pub line_content: String,
+    /// Notebook cell the import was found in, 0-based and counting markdown
+    /// cells as nbformat does; `line_number` is then relative to the cell
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cell_index: Option<usize>,
+    #[serde(default)]
+    pub kind: UsageKind,
+    /// Workspace package whose directory contains the file, in a monorepo
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub workspace: Option<String>,
+    /// True when the file matches the configured test paths
+    #[serde(default)]
+    pub is_test: bool,
+}
+
+/// A version forced onto a package by the manifest, e.g. npm `overrides`,
+/// yarn `resolutions` or `pnpm.overrides`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DependencyOverride {
+    /// Package whose version is forced
+    pub package: String,
+    /// Key as written in the manifest (`**/lodash`, `foo@<2`, `a>b`)
+    pub selector: String,
+    pub version: String,
+    /// Manifest field the override comes from
+    pub source: String,
+    /// Commit that introduced the override
+    pub git_info: Option<GitInfo>,
+}
+
+/// The runtime package that a typings-only package (e.g. `@types/lodash`) provides types for
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TypedPackage {
+    pub name: String,
+    /// False when the runtime package is no longer a dependency, leaving the typings orphaned
+    pub is_declared: bool,
 }
 
 /// Complete analysis result for a dependency
@@ -109,6 +346,30 @@ pub struct DependencyAnalysis {
     pub dependency: Dependency,
     pub git_info: Option<GitInfo>,
     pub usage_info: UsageInfo,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub typed_package: Option<TypedPackage>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub overrides: Vec<DependencyOverride>,
+    /// Releases that shipped the dependency, when the repository has release tags
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub releases: Option<ReleaseRange>,
+    /// Blame of the dependency's manifest entry
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub blame: Option<EntryBlame>,
+    /// Set when the dependency is no longer declared, from the manifest history
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub removed: Option<RemovedDependency>,
+    /// Set when the repository is a shallow clone, whose history may not
+    /// reach back to the commits reported
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shallow: Option<ShallowHistory>,
+    /// Set when the dependency was added in the working copy, which leaves
+    /// no history to report
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub local_change: Option<LocalChange>,
+    /// Reason recorded with `explain set`, which outranks the commit message
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rationale: Option<Rationale>,
 }
 
 /// Query parameters
@@ -118,6 +379,14 @@ pub struct DependencyQuery {
     pub repo_path: PathBuf,
     pub include_git_history: bool,
     pub scan_usage: bool,
+    /// Analyze only this manifest (relative to `repo_path`) instead of detecting them
+    pub manifest: Option<PathBuf>,
+    /// Only consider manifests of this ecosystem, or parse `manifest` as one
+    pub ecosystem: Option<EcosystemType>,
+    /// Reuse and update the analysis cache in the git directory
+    pub use_cache: bool,
+    /// Read manifests and sources from this git revision instead of the working directory
+    pub revision: Option<String>,
 }
 
 impl DependencyQuery {
@@ -127,6 +396,10 @@ impl DependencyQuery {
             repo_path,
             include_git_history: true,
             scan_usage: true,
+            manifest: None,
+            ecosystem: None,
+            use_cache: true,
+            revision: None,
         }
     }
 
@@ -141,6 +414,10 @@ impl DependencyQuery {
             repo_path,
             include_git_history,
             scan_usage,
+            manifest: None,
+            ecosystem: None,
+            use_cache: true,
+            revision: None,
         }
     }
 }
@@ -0,0 +1,342 @@
+use crate::core::dependency::{Dependency, DependencyType, EcosystemType, GitInfo};
+use crate::core::git_analyzer::CommitChanges;
+use regex::Regex;
+use serde::Serialize;
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+
+/// How a dependency changed between two revisions
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Upgraded,
+    Downgraded,
+    /// The version changed, but which one is newer could not be told
+    Changed,
+    /// Only the dependency type changed, e.g. from development to direct
+    Reclassified,
+}
+
+impl ChangeKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChangeKind::Added => "Added",
+            ChangeKind::Removed => "Removed",
+            ChangeKind::Upgraded => "Upgraded",
+            ChangeKind::Downgraded => "Downgraded",
+            ChangeKind::Changed => "Changed",
+            ChangeKind::Reclassified => "Reclassified",
+        }
+    }
+}
+
+/// A dependency as declared at one of the compared revisions
+#[derive(Debug, Clone, Serialize)]
+pub struct DependencyState {
+    /// Version requirement from the manifest
+    pub version: String,
+    /// Version pinned by the lockfile, when there is one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub locked: Option<String>,
+    pub dependency_type: DependencyType,
+}
+
+impl DependencyState {
+    /// The locked version when known, otherwise the requirement
+    pub fn effective_version(&self) -> &str {
+        self.locked.as_deref().unwrap_or(&self.version)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DependencyChange {
+    pub name: String,
+    pub ecosystem: EcosystemType,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub manifest: Option<PathBuf>,
+    pub kind: ChangeKind,
+    pub before: Option<DependencyState>,
+    pub after: Option<DependencyState>,
+    /// Commits in the range that touched this dependency, oldest first
+    pub commits: Vec<GitInfo>,
+}
+
+/// Dependency changes between two revisions
+#[derive(Debug, Clone, Serialize)]
+pub struct DependencyDiff {
+    pub from: String,
+    pub to: String,
+    pub from_commit: String,
+    pub to_commit: String,
+    pub changes: Vec<DependencyChange>,
+}
+
+/// A parsed dependency with the version its lockfile pins
+pub type LockedDependency = (Dependency, Option<String>);
+
+/// Compare the dependencies declared at two revisions.
+///
+/// Dependencies are matched by ecosystem, manifest and name. Changes are
+/// sorted by manifest, kind and name; commits are attached separately.
+pub fn compare(before: &[LockedDependency], after: &[LockedDependency]) -> Vec<DependencyChange> {
+    let key = |dep: &Dependency| (dep.ecosystem, dep.manifest.clone(), dep.name.clone());
+    let state = |(dep, locked): &LockedDependency| DependencyState {
+        version: dep.version.clone(),
+        locked: locked.clone(),
+        dependency_type: dep.dependency_type,
+    };
+
+    // A name declared twice in one manifest (e.g. also as a dev dependency)
+    // is compared by its first declaration
+    let mut old: HashMap<_, &LockedDependency> = HashMap::new();
+    for entry in before {
+        old.entry(key(&entry.0)).or_insert(entry);
+    }
+
+    let mut changes = Vec::new();
+    let mut seen = HashSet::new();
+
+    for entry in after {
+        let k = key(&entry.0);
+        if !seen.insert(k.clone()) {
+            continue;
+        }
+
+        let after_state = state(entry);
+        let before_state = old.remove(&k).map(state);
+        let kind = match &before_state {
+            None => ChangeKind::Added,
+            Some(before_state) => match classify(before_state, &after_state) {
+                Some(kind) => kind,
+                None => continue,
+            },
+        };
+
+        changes.push(DependencyChange {
+            name: entry.0.name.clone(),
+            ecosystem: entry.0.ecosystem,
+            manifest: entry.0.manifest.clone(),
+            kind,
+            before: before_state,
+            after: Some(after_state),
+            commits: Vec::new(),
+        });
+    }
+
+    for (_, entry) in old {
+        changes.push(DependencyChange {
+            name: entry.0.name.clone(),
+            ecosystem: entry.0.ecosystem,
+            manifest: entry.0.manifest.clone(),
+            kind: ChangeKind::Removed,
+            before: Some(state(entry)),
+            after: None,
+            commits: Vec::new(),
+        });
+    }
+
+    changes.sort_by(|a, b| {
+        a.manifest
+            .cmp(&b.manifest)
+            .then(a.kind.cmp(&b.kind))
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    changes
+}
+
+/// Attach the commits whose changes to the manifest or its lockfile mention
+/// each dependency. `lockfiles` maps manifests to their lockfile, both
+/// relative to the repository root like the hunk paths.
+pub fn attribute_commits(
+    changes: &mut [DependencyChange],
+    commits: &[CommitChanges],
+    lockfiles: &HashMap<PathBuf, PathBuf>,
+) {
+    for change in changes {
+        let pattern = mention_pattern(&change.name);
+        let manifest = change.manifest.as_ref();
+        let lockfile = manifest.and_then(|m| lockfiles.get(m));
+        // Moving an entry to another section may show up as the section
+        // header moving around it instead
+        let match_context = change.kind == ChangeKind::Reclassified;
+
+        change.commits = commits
+            .iter()
+            .filter(|commit| {
+                commit.hunks.iter().any(|hunk| {
+                    // Lockfile entries often change only their version line,
+                    // so the surrounding context that names the package counts
+                    if Some(&hunk.file) == lockfile {
+                        pattern.is_match(&hunk.text)
+                    } else {
+                        let text = if match_context {
+                            &hunk.text
+                        } else {
+                            &hunk.changed
+                        };
+                        Some(&hunk.file) == manifest && pattern.is_match(text)
+                    }
+                })
+            })
+            .map(|commit| commit.info.clone())
+            .collect();
+    }
+}
+
+/// Match the package name on its own, not as part of a longer name such as
+/// `serde_json` or `@types/react`
+fn mention_pattern(name: &str) -> Regex {
+    let pattern = format!(
+        r"(?m)(?:^|[^\w@./-]|node_modules/){}(?:$|[^\w./-])",
+        regex::escape(name)
+    );
+    Regex::new(&pattern).expect("escaped name is a valid regex")
+}
+
+fn classify(before: &DependencyState, after: &DependencyState) -> Option<ChangeKind> {
+    let version_changed = before.version != after.version || before.locked != after.locked;
+    if version_changed {
+        return Some(
+            match compare_versions(before.effective_version(), after.effective_version()) {
+                Some(Ordering::Less) => ChangeKind::Upgraded,
+                Some(Ordering::Greater) => ChangeKind::Downgraded,
+                _ => ChangeKind::Changed,
+            },
+        );
+    }
+    if before.dependency_type != after.dependency_type {
+        return Some(ChangeKind::Reclassified);
+    }
+    None
+}
+
+/// Compare the leading numeric components of two versions or requirements
+/// (`^1.2`, `>=0.4.1`, `v2.0.0`). Returns `None` when either has none or
+/// they only differ in other parts, such as pre-release tags.
+fn compare_versions(a: &str, b: &str) -> Option<Ordering> {
+    let (a, b) = (numeric_parts(a)?, numeric_parts(b)?);
+    let len = a.len().max(b.len());
+    let pad = |v: &[u64]| {
+        (0..len)
+            .map(|i| v.get(i).copied().unwrap_or(0))
+            .collect::<Vec<_>>()
+    };
+    match pad(&a).cmp(&pad(&b)) {
+        Ordering::Equal => None,
+        ordering => Some(ordering),
+    }
+}
+
+fn numeric_parts(version: &str) -> Option<Vec<u64>> {
+    let start = version.find(|c: char| c.is_ascii_digit())?;
+    let parts: Vec<u64> = version[start..]
+        .split('.')
+        .map_while(|part| {
+            let digits: String = part.chars().take_while(|c| c.is_ascii_digit()).collect();
+            digits.parse().ok()
+        })
+        .collect();
+    (!parts.is_empty()).then_some(parts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn locked(
+        name: &str,
+        version: &str,
+        locked: Option<&str>,
+        dependency_type: DependencyType,
+    ) -> LockedDependency {
+        let mut dep = Dependency::new(
+            name.to_string(),
+            version.to_string(),
+            EcosystemType::Node,
+            dependency_type,
+        );
+        dep.manifest = Some(PathBuf::from("package.json"));
+        (dep, locked.map(String::from))
+    }
+
+    fn kinds(changes: &[DependencyChange]) -> Vec<(&str, ChangeKind)> {
+        changes.iter().map(|c| (c.name.as_str(), c.kind)).collect()
+    }
+
+    #[test]
+    fn changes_are_classified() {
+        let before = [
+            locked("kept", "^1.0", None, DependencyType::Direct),
+            locked("newer", "^1.0", None, DependencyType::Direct),
+            locked("older", "2.0.0", None, DependencyType::Direct),
+            locked("moved", "^1.0", None, DependencyType::Dev),
+            locked("dropped", "^1.0", None, DependencyType::Direct),
+        ];
+        let after = [
+            locked("kept", "^1.0", None, DependencyType::Direct),
+            locked("newer", "^1.2", None, DependencyType::Direct),
+            locked("older", "1.5.0", None, DependencyType::Direct),
+            locked("moved", "^1.0", None, DependencyType::Direct),
+            locked("fresh", "^3.0", None, DependencyType::Dev),
+        ];
+
+        assert_eq!(
+            kinds(&compare(&before, &after)),
+            [
+                ("fresh", ChangeKind::Added),
+                ("dropped", ChangeKind::Removed),
+                ("newer", ChangeKind::Upgraded),
+                ("older", ChangeKind::Downgraded),
+                ("moved", ChangeKind::Reclassified),
+            ]
+        );
+    }
+
+    #[test]
+    fn locked_versions_take_precedence_over_requirements() {
+        let before = [
+            locked("relocked", "^1.2", Some("1.2.5"), DependencyType::Direct),
+            locked("tightened", "^1.0", Some("1.4.0"), DependencyType::Direct),
+        ];
+        let after = [
+            locked("relocked", "^1.2", Some("1.2.3"), DependencyType::Direct),
+            locked("tightened", "^1.4", Some("1.4.0"), DependencyType::Direct),
+        ];
+
+        assert_eq!(
+            kinds(&compare(&before, &after)),
+            [
+                ("relocked", ChangeKind::Downgraded),
+                ("tightened", ChangeKind::Changed),
+            ]
+        );
+    }
+
+    #[test]
+    fn requirements_of_the_same_version_are_changed() {
+        let before = [locked("lodash", "^1.2", None, DependencyType::Direct)];
+        let after = [locked("lodash", "~1.2", None, DependencyType::Direct)];
+
+        assert_eq!(
+            kinds(&compare(&before, &after)),
+            [("lodash", ChangeKind::Changed)]
+        );
+    }
+
+    #[test]
+    fn mentions_are_whole_names() {
+        let serde = mention_pattern("serde");
+        assert!(serde.is_match("serde = \"1\""));
+        assert!(serde.is_match("+name = \"serde\""));
+        assert!(!serde.is_match("serde_json = \"1\""));
+
+        let react = mention_pattern("react");
+        assert!(react.is_match("    \"react\": \"^18.2.0\","));
+        assert!(react.is_match("    \"node_modules/react\": {"));
+        assert!(!react.is_match("    \"@types/react\": \"^18.2.0\","));
+        assert!(!react.is_match("    \"react-dom\": \"^18.2.0\","));
+    }
+}
@@ -12,6 +12,9 @@ pub enum DependencyBlameError {
     #[error("Failed to parse dependency file '{file}': {reason}")]
     ParseError { file: String, reason: String },
 
+    #[error("Invalid config file '{file}': {reason}")]
+    ConfigError { file: String, reason: String },
+
     #[error("Dependency '{0}' not found in project")]
     DependencyNotFound(String),
 
@@ -21,6 +24,12 @@ pub enum DependencyBlameError {
     #[error("Could not detect ecosystem from path: {0}")]
     EcosystemDetectionFailed(PathBuf),
 
+    #[error("Revision '{0}' not found")]
+    RevisionNotFound(String),
+
+    #[error("Commit adds dependencies without a reason: {}", .0.join(", "))]
+    MissingDependencyReason(Vec<String>),
+
     #[error("Git operation failed: {0}")]
     GitError(#[from] git2::Error),
 
@@ -1,166 +1,1307 @@
-use crate::core::dependency::GitInfo;
+use crate::core::cache::{AnalysisCache, ManifestIndex};
+use crate::core::commit_message::{self, IssuePatterns};
+use crate::core::dependency::{
+    Dependency, EcosystemType, EntryBlame, GitInfo, LocalChange, LocalState, Release, ReleaseRange,
+    ShallowHistory,
+};
 use crate::core::error::{DependencyBlameError, Result};
+use crate::core::merge;
+use crate::core::rationale;
+use crate::ecosystems::traits::DependencyParser;
+use crate::utils::manifest::{self, EntryPattern};
+use crate::utils::patterns;
 use chrono::{DateTime, Utc};
-use git2::{DiffOptions, Oid, Repository};
-use std::path::Path;
+use git2::{
+    Blame, BlameOptions, Delta, DiffFindOptions, DiffOptions, Mailmap, ObjectType, Oid, Repository,
+    Signature, Tree,
+};
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock};
+use tempfile::TempDir;
+
+/// A commit together with the hunks it changed in the files of interest
+#[derive(Debug, Clone)]
+pub struct CommitChanges {
+    pub info: GitInfo,
+    pub hunks: Vec<DiffHunk>,
+}
+
+#[derive(Debug, Clone)]
+pub struct DiffHunk {
+    pub file: PathBuf,
+    /// Added and removed lines
+    pub changed: String,
+    /// Changed lines with their surrounding context
+    pub text: String,
+}
+
+/// Where a manifest line came from according to blame
+struct LineOrigin {
+    commit: Oid,
+    path: PathBuf,
+    /// 1-based line in `path` as of `commit`
+    line: usize,
+}
+
+/// The commit that introduced something into a file
+struct Introduction<'r> {
+    commit: git2::Commit<'r>,
+    /// The file's path in that commit
+    path: PathBuf,
+    /// Merge commit that brought `commit` into the mainline
+    merge: Option<git2::Commit<'r>>,
+}
 
 pub struct GitAnalyzer {
     repo: Repository,
+    cache: Option<Arc<AnalysisCache>>,
+    mailmap: Option<Mailmap>,
+    issue_patterns: IssuePatterns,
+    /// Commits at which the history of a shallow clone ends
+    shallow: Vec<Oid>,
+    /// Commit whose history is analyzed instead of HEAD's, and the
+    /// directory its files were written to
+    revision: Option<(Oid, PathBuf)>,
+    /// Where old revisions of manifests are written for parsing
+    scratch: OnceLock<TempDir>,
 }
 
 impl GitAnalyzer {
     pub fn new(repo_path: &Path) -> Result<Self> {
         let repo = Repository::discover(repo_path)
             .map_err(|_| DependencyBlameError::GitRepoNotFound(repo_path.to_path_buf()))?;
-        Ok(Self { repo })
+        let mailmap = repo.mailmap().ok();
+        let shallow = shallow_boundaries(&repo);
+        Ok(Self {
+            repo,
+            cache: None,
+            mailmap,
+            issue_patterns: IssuePatterns::default(),
+            shallow,
+            revision: None,
+            scratch: OnceLock::new(),
+        })
+    }
+
+    /// Reuse and extend the manifest history index stored in `cache`
+    pub fn with_cache(mut self, cache: Arc<AnalysisCache>) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Recognize these ticket keys in commit messages, besides `#123` and URLs
+    pub fn with_issue_patterns(mut self, issue_patterns: IssuePatterns) -> Self {
+        self.issue_patterns = issue_patterns;
+        self
+    }
+
+    /// Analyze the history of `commit` instead of HEAD. Manifest paths are
+    /// then given inside `snapshot_dir`, see [`RevisionSnapshot`].
+    ///
+    /// [`RevisionSnapshot`]: crate::core::revision::RevisionSnapshot
+    pub fn with_revision(mut self, commit: Oid, snapshot_dir: PathBuf) -> Self {
+        self.revision = Some((commit, snapshot_dir));
+        self
     }
 
-    /// Find the commit that introduced a dependency
+    /// Find the commit that introduced a dependency.
+    ///
+    /// When the dependency was moved into this manifest from another one
+    /// that `parser` can read, such as when a workspace was split up, its
+    /// introduction in that manifest is reported instead.
     pub fn find_dependency_introduction(
         &self,
         dependency_file: &Path,
         dependency_name: &str,
+        parser: &dyn DependencyParser,
     ) -> Result<Option<GitInfo>> {
-        // Get the file path relative to the repository root
-        let repo_path = self.repo.workdir().ok_or_else(|| {
-            DependencyBlameError::Other("Repository has no working directory".to_string())
-        })?;
+        let mut relative_path = self.relative_path(dependency_file)?;
+        let mut visited = vec![relative_path.clone()];
 
-        let relative_path = dependency_file
-            .strip_prefix(repo_path)
-            .unwrap_or(dependency_file);
+        loop {
+            let introduction = match self
+                .introduction_in(&relative_path, |content| content.contains(dependency_name))?
+            {
+                Some(found) => found,
+                None => return Ok(None),
+            };
 
-        // Walk through the commit history for this file
-        let mut revwalk = self.repo.revwalk()?;
-        revwalk.push_head()?;
+            let moved_from = self.moved_from(
+                &introduction.commit,
+                &introduction.path,
+                dependency_name,
+                parser,
+            )?;
+            match moved_from {
+                Some(source) if !visited.contains(&source) => {
+                    visited.push(source.clone());
+                    relative_path = source;
+                }
+                _ => return Ok(Some(self.introduction_info(&introduction)?)),
+            }
+        }
+    }
 
-        // Set up to walk commits in chronological order (oldest first)
-        revwalk.set_sorting(git2::Sort::TIME | git2::Sort::REVERSE)?;
+    /// Find the first commit in which the dependency file's content satisfies `is_present`
+    pub fn find_introduction<F>(
+        &self,
+        dependency_file: &Path,
+        is_present: F,
+    ) -> Result<Option<GitInfo>>
+    where
+        F: Fn(&str) -> bool,
+    {
+        let relative_path = self.relative_path(dependency_file)?;
+        match self.introduction_in(&relative_path, is_present)? {
+            Some(introduction) => Ok(Some(self.introduction_info(&introduction)?)),
+            None => Ok(None),
+        }
+    }
 
-        let mut last_commit_without_dep: Option<Oid> = None;
-        let mut first_commit_with_dep: Option<Oid> = None;
+    /// Find where the file, under any of its names, first satisfied
+    /// `is_present`, following the topology of the history.
+    ///
+    /// The mainline (the first-parent history of the analyzed revision) is
+    /// searched first, so a branch that was merged earlier wins over one
+    /// with older commits that was merged later. When a merge brought the
+    /// change in, the commit on the merged branch that made it is reported
+    /// together with the merge.
+    fn introduction_in<F>(
+        &self,
+        relative_path: &Path,
+        is_present: F,
+    ) -> Result<Option<Introduction<'_>>>
+    where
+        F: Fn(&str) -> bool,
+    {
+        let index = self.manifest_index(relative_path)?;
+        let mut paths = vec![relative_path.to_path_buf()];
+        for path in index.paths() {
+            if !paths.contains(&path) {
+                paths.push(path);
+            }
+        }
+        let mut checked = HashMap::new();
+        let mut declaring_path = |tree: &Tree| -> Result<Option<PathBuf>> {
+            for path in &paths {
+                let blob = match blob_at(tree, path) {
+                    Some(blob) => blob,
+                    None => continue,
+                };
+                let present = match checked.get(&blob) {
+                    Some(present) => *present,
+                    None => {
+                        let content = self.repo.find_blob(blob)?;
+                        let present = is_present(&String::from_utf8_lossy(content.content()));
+                        checked.insert(blob, present);
+                        present
+                    }
+                };
+                if present {
+                    return Ok(Some(path.clone()));
+                }
+            }
+            Ok(None)
+        };
 
+        let mut mainline = self.repo.revwalk()?;
+        mainline.push(self.start_commit()?)?;
+        mainline.simplify_first_parent()?;
+        mainline.set_sorting(git2::Sort::TOPOLOGICAL | git2::Sort::REVERSE)?;
+
+        let mut landed = None;
+        for oid_result in mainline {
+            let commit = self.repo.find_commit(oid_result?)?;
+            if let Some(path) = declaring_path(&commit.tree()?)? {
+                landed = Some((commit, path));
+                break;
+            }
+        }
+        let (landed, path) = match landed {
+            Some(landed) => landed,
+            None => return self.first_revision_with(&index, is_present),
+        };
+        if landed.parent_count() < 2 {
+            return Ok(Some(Introduction {
+                commit: landed,
+                path,
+                merge: None,
+            }));
+        }
+
+        // The first commit on the merged branches that made the change
+        let mut branch = self.repo.revwalk()?;
+        branch.push(landed.id())?;
+        branch.hide(landed.parent_id(0)?)?;
+        branch.set_sorting(git2::Sort::TOPOLOGICAL | git2::Sort::TIME | git2::Sort::REVERSE)?;
+
+        for oid_result in branch {
+            let commit = self.repo.find_commit(oid_result?)?;
+            if commit.parent_count() > 1 {
+                continue;
+            }
+            let path = match declaring_path(&commit.tree()?)? {
+                Some(path) => path,
+                None => continue,
+            };
+            let parent_declares = match commit.parents().next() {
+                Some(parent) => declaring_path(&parent.tree()?)?.is_some(),
+                None => false,
+            };
+            if !parent_declares {
+                return Ok(Some(Introduction {
+                    commit,
+                    path,
+                    merge: Some(landed),
+                }));
+            }
+        }
+
+        // The merge itself made the change, e.g. while resolving a conflict
+        Ok(Some(Introduction {
+            commit: landed,
+            path,
+            merge: None,
+        }))
+    }
+
+    /// The first indexed revision of the file that satisfies `is_present`,
+    /// for changes that never reached the mainline
+    fn first_revision_with<F>(
+        &self,
+        index: &ManifestIndex,
+        is_present: F,
+    ) -> Result<Option<Introduction<'_>>>
+    where
+        F: Fn(&str) -> bool,
+    {
+        for revision in &index.entries {
+            let (blob, path) = match (&revision.blob, &revision.path) {
+                (Some(blob), Some(path)) => (self.repo.find_blob(Oid::from_str(blob)?)?, path),
+                _ => continue,
+            };
+            if is_present(&String::from_utf8_lossy(blob.content())) {
+                let commit = self.repo.find_commit(Oid::from_str(&revision.commit)?)?;
+                return Ok(Some(Introduction {
+                    commit,
+                    path: PathBuf::from(path),
+                    merge: None,
+                }));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Commit information for an introduction, with the merge that brought
+    /// it into the mainline and the pull request it belongs to
+    fn introduction_info(&self, introduction: &Introduction) -> Result<GitInfo> {
+        let mut info = self.extract_commit_info(&introduction.commit, &introduction.path)?;
+        if let Some(merge) = &introduction.merge {
+            let merge_info = self.extract_commit_info(merge, &introduction.path)?;
+            if let Some(pull_request) = merge::parse_pull_request(&merge_info.message) {
+                info.pull_request = Some(pull_request);
+            }
+            info.merged_in = Some(Box::new(merge_info));
+        }
+        Ok(info)
+    }
+
+    /// Every commit in the analyzed history that gave the file new content or
+    /// deleted it, oldest first, with the file's blob after that commit
+    pub fn file_revisions(&self, dependency_file: &Path) -> Result<Vec<(GitInfo, Option<Oid>)>> {
+        let relative_path = self.relative_path(dependency_file)?;
+        let index = self.manifest_index(&relative_path)?;
+
+        index
+            .entries
+            .iter()
+            .map(|revision| {
+                let commit = self.repo.find_commit(Oid::from_str(&revision.commit)?)?;
+                let blob = revision.blob.as_deref().map(Oid::from_str).transpose()?;
+                let path = revision
+                    .path
+                    .as_ref()
+                    .map(PathBuf::from)
+                    .unwrap_or_else(|| relative_path.clone());
+                Ok((self.extract_commit_info(&commit, &path)?, blob))
+            })
+            .collect()
+    }
+
+    /// Files that were deleted somewhere in the analyzed history and are
+    /// absent from the analyzed revision, such as the manifest of a removed
+    /// workspace member. `is_wanted` selects them by their path relative to
+    /// the repository root; a file that was renamed lives on under its new
+    /// name and is left out. Paths are returned like the ones on disk, so
+    /// they can be given to [`file_revisions`](Self::file_revisions).
+    pub fn deleted_files<F>(&self, is_wanted: F) -> Result<Vec<PathBuf>>
+    where
+        F: Fn(&Path) -> bool,
+    {
+        let start = self.repo.find_commit(self.start_commit()?)?;
+        let start_tree = start.tree()?;
+        let mut revwalk = self.repo.revwalk()?;
+        revwalk.push(start.id())?;
+
+        let mut deleted = BTreeSet::new();
         for oid_result in revwalk {
-            let oid = oid_result?;
-            let commit = self.repo.find_commit(oid)?;
+            let commit = self.repo.find_commit(oid_result?)?;
+            // A merge takes its deletions from the branches it joins
+            if commit.parent_count() != 1 {
+                continue;
+            }
+            let mut diff = self.repo.diff_tree_to_tree(
+                Some(&commit.parent(0)?.tree()?),
+                Some(&commit.tree()?),
+                None,
+            )?;
+            let is_wanted_deletion = |delta: &git2::DiffDelta| {
+                delta.status() == Delta::Deleted && delta.old_file().path().is_some_and(&is_wanted)
+            };
+            // Rename detection is only worth it for commits that delete a
+            // wanted file
+            if !diff.deltas().any(|delta| is_wanted_deletion(&delta)) {
+                continue;
+            }
+            let mut find_options = DiffFindOptions::new();
+            find_options.renames(true);
+            diff.find_similar(Some(&mut find_options))?;
 
-            // Get the tree for this commit
-            let tree = commit.tree()?;
+            for delta in diff.deltas().filter(is_wanted_deletion) {
+                if let Some(path) = delta.old_file().path() {
+                    if blob_at(&start_tree, path).is_none() {
+                        deleted.insert(path.to_path_buf());
+                    }
+                }
+            }
+        }
 
-            // Try to find the dependency file in this commit
-            if let Ok(entry) = tree.get_path(relative_path) {
-                // File exists in this commit, check if it contains the dependency
-                let object = entry.to_object(&self.repo)?;
-                if let Some(blob) = object.as_blob() {
-                    let content = String::from_utf8_lossy(blob.content());
-
-                    if content.contains(dependency_name) {
-                        // This commit has the dependency
-                        if first_commit_with_dep.is_none() {
-                            first_commit_with_dep = Some(oid);
-
-                            // If this is the first commit we're seeing,
-                            // it means the dependency was added in the first commit
-                            if last_commit_without_dep.is_none() {
-                                break;
-                            }
-                        }
-                    } else {
-                        // This commit doesn't have the dependency yet
-                        last_commit_without_dep = Some(oid);
+        let base = match &self.revision {
+            Some((_, snapshot_dir)) => snapshot_dir.clone(),
+            None => self.workdir()?.to_path_buf(),
+        };
+        Ok(deleted.into_iter().map(|path| base.join(path)).collect())
+    }
+
+    /// Write a blob to a temporary directory as `file_name`, so that parsers
+    /// which read files can read old revisions. Written blobs are reused
+    /// until the analyzer is dropped, which removes the directory; nothing
+    /// is written into the repository.
+    pub fn blob_file(&self, blob: Oid, file_name: &str) -> Result<PathBuf> {
+        let scratch = match self.scratch.get() {
+            Some(scratch) => scratch,
+            None => {
+                let dir = tempfile::Builder::new()
+                    .prefix("dependency-blame-")
+                    .tempdir()?;
+                self.scratch.get_or_init(|| dir)
+            }
+        };
+        let dir = scratch.path().join(blob.to_string());
+        let path = dir.join(file_name);
+        if !path.is_file() {
+            fs::create_dir_all(&dir)?;
+            fs::write(&path, self.repo.find_blob(blob)?.content())?;
+        }
+        Ok(path)
+    }
+
+    /// Index the revisions of a file in HEAD's history, oldest first,
+    /// following it back through renames.
+    ///
+    /// With a cache, an index built for an ancestor of HEAD is extended with
+    /// the new commits only; after a rewrite of history it is rebuilt.
+    fn manifest_index(&self, relative_path: &Path) -> Result<ManifestIndex> {
+        let head = self.start_commit()?;
+
+        let cached = self
+            .cache
+            .as_ref()
+            .and_then(|cache| cache.manifest_index(relative_path))
+            .and_then(|index| Some((Oid::from_str(&index.head).ok()?, index)));
 
-                        // If we already found a commit with the dep, we went too far
-                        if first_commit_with_dep.is_some() {
-                            break;
+        let shallow: Vec<String> = self.shallow.iter().map(Oid::to_string).collect();
+        let mut index = match cached {
+            // Fetching more history leaves HEAD as it is
+            Some((_, index)) if index.shallow != shallow => ManifestIndex::default(),
+            Some((cached_head, index)) if cached_head == head => return Ok(index),
+            Some((cached_head, index)) if self.repo.graph_descendant_of(head, cached_head)? => {
+                index
+            }
+            _ => ManifestIndex::default(),
+        };
+
+        // Names the file had, newest first. A commit may be on a branch that
+        // still uses an older name, so every name is tried.
+        let mut paths = vec![relative_path.to_path_buf()];
+        for path in index.paths() {
+            if !paths.contains(&path) {
+                paths.push(path);
+            }
+        }
+
+        // Walk newest first, so a rename is seen before the commits that
+        // used the old name
+        let mut revwalk = self.repo.revwalk()?;
+        revwalk.push(head)?;
+        if !index.head.is_empty() {
+            revwalk.hide(Oid::from_str(&index.head)?)?;
+        }
+        revwalk.set_sorting(git2::Sort::TOPOLOGICAL | git2::Sort::TIME)?;
+
+        let mut revisions = Vec::new();
+        for oid_result in revwalk {
+            let commit = self.repo.find_commit(oid_result?)?;
+            let tree = commit.tree()?;
+            let file = paths
+                .iter()
+                .find_map(|path| Some((blob_at(&tree, path)?, path.clone())));
+
+            if let Some((_, path)) = &file {
+                let parent_has_file = match commit.parents().next() {
+                    Some(parent) => {
+                        let parent_tree = parent.tree()?;
+                        paths.iter().any(|p| blob_at(&parent_tree, p).is_some())
+                    }
+                    None => true,
+                };
+                if !parent_has_file {
+                    // Only a rename continues the file's history; a copy's
+                    // source keeps its own
+                    if let Some(old_path) = self.renamed_from(&commit, path, false)? {
+                        if !paths.contains(&old_path) {
+                            paths.push(old_path);
                         }
                     }
                 }
-            } else {
-                // File doesn't exist in this commit yet
-                last_commit_without_dep = Some(oid);
             }
+
+            revisions.push((commit.id(), file));
+        }
+
+        for (commit, file) in revisions.into_iter().rev() {
+            index.push(
+                commit,
+                file.as_ref().map(|(blob, path)| (*blob, path.as_path())),
+            );
         }
 
-        // The dependency was introduced in first_commit_with_dep
-        if let Some(commit_oid) = first_commit_with_dep {
-            let commit = self.repo.find_commit(commit_oid)?;
-            let git_info = self.extract_commit_info(&commit, relative_path)?;
-            return Ok(Some(git_info));
+        index.head = head.to_string();
+        index.shallow = shallow;
+        if let Some(cache) = &self.cache {
+            cache.set_manifest_index(relative_path, index.clone());
+        }
+        Ok(index)
+    }
+
+    /// The path `relative_path` was renamed from in `commit`, or also copied
+    /// from with `copies`, according to git's similarity detection against
+    /// the first parent
+    fn renamed_from(
+        &self,
+        commit: &git2::Commit,
+        relative_path: &Path,
+        copies: bool,
+    ) -> Result<Option<PathBuf>> {
+        let parent_tree = match commit.parents().next() {
+            Some(parent) => parent.tree()?,
+            None => return Ok(None),
+        };
+        let mut diff =
+            self.repo
+                .diff_tree_to_tree(Some(&parent_tree), Some(&commit.tree()?), None)?;
+        let mut find_options = DiffFindOptions::new();
+        find_options.renames(true).copies(copies);
+        diff.find_similar(Some(&mut find_options))?;
+
+        Ok(diff
+            .deltas()
+            .filter(|delta| match delta.status() {
+                Delta::Renamed => true,
+                Delta::Copied => copies,
+                _ => false,
+            })
+            .filter(|delta| delta.new_file().path() == Some(relative_path))
+            .find_map(|delta| delta.old_file().path().map(Path::to_path_buf)))
+    }
+
+    /// Another manifest that `commit` moved the dependency out of: one that
+    /// declared it before the commit and no longer does after it
+    fn moved_from(
+        &self,
+        commit: &git2::Commit,
+        relative_path: &Path,
+        dependency_name: &str,
+        parser: &dyn DependencyParser,
+    ) -> Result<Option<PathBuf>> {
+        let parent_tree = match commit.parents().next() {
+            Some(parent) => parent.tree()?,
+            None => return Ok(None),
+        };
+        let diff = self
+            .repo
+            .diff_tree_to_tree(Some(&parent_tree), Some(&commit.tree()?), None)?;
+
+        let entry = EntryPattern::new(dependency_name);
+        let declares = |blob: Oid| -> Result<bool> {
+            if blob.is_zero() {
+                return Ok(false);
+            }
+            let blob = self.repo.find_blob(blob)?;
+            let content = String::from_utf8_lossy(blob.content());
+            Ok(entry.lines(&content).is_some())
+        };
+
+        for delta in diff.deltas() {
+            let old_path = match delta.old_file().path() {
+                Some(path) if path != relative_path && parser.can_parse(path) => path,
+                _ => continue,
+            };
+            if declares(delta.old_file().id())? && !declares(delta.new_file().id())? {
+                return Ok(Some(old_path.to_path_buf()));
+            }
         }
 
         Ok(None)
     }
 
-    /// Get blame information for the entire dependency file
-    pub fn get_dependency_history(&self, dependency_file: &Path) -> Result<Vec<GitInfo>> {
-        let repo_path = self.repo.workdir().ok_or_else(|| {
-            DependencyBlameError::Other("Repository has no working directory".to_string())
-        })?;
+    /// The releases that shipped a dependency added in `added` and, if it
+    /// is gone, removed in `removed` (commit hashes). Releases are the tags
+    /// matching the glob `tag_pattern`, or all tags.
+    ///
+    /// The first release is the earliest one with the addition and without
+    /// the removal; the range ends at the earliest later release with the
+    /// removal. Returns `None` when there are no release tags.
+    pub fn release_range(
+        &self,
+        added: &str,
+        removed: Option<&str>,
+        tag_pattern: Option<&str>,
+    ) -> Result<Option<ReleaseRange>> {
+        let releases = self.releases(tag_pattern)?;
+        if releases.is_empty() {
+            return Ok(None);
+        }
 
-        let relative_path = dependency_file
-            .strip_prefix(repo_path)
-            .unwrap_or(dependency_file);
+        let contains = |tagged: Oid, commit: Oid| -> Result<bool> {
+            Ok(tagged == commit || self.repo.graph_descendant_of(tagged, commit)?)
+        };
+        let added = Oid::from_str(added)?;
+        let removed = removed.map(Oid::from_str).transpose()?;
+
+        // A release shipped the dependency when it has the commit that added
+        // it but not the one that removed it
+        let mut first = None;
+        for (index, (_, tagged)) in releases.iter().enumerate() {
+            let removed_in = match removed {
+                Some(removed) => contains(*tagged, removed)?,
+                None => false,
+            };
+            if contains(*tagged, added)? && !removed_in {
+                first = Some(index);
+                break;
+            }
+        }
+        let first = match first {
+            Some(first) => first,
+            None => {
+                return Ok(Some(ReleaseRange {
+                    first: None,
+                    first_without: None,
+                }))
+            }
+        };
 
+        let mut first_without = None;
+        if let Some(removed) = removed {
+            for (release, tagged) in &releases[first + 1..] {
+                if contains(*tagged, removed)? {
+                    first_without = Some(release.clone());
+                    break;
+                }
+            }
+        }
+
+        Ok(Some(ReleaseRange {
+            first: Some(releases[first].0.clone()),
+            first_without,
+        }))
+    }
+
+    /// Tags matching `tag_pattern` with the commits they point to, oldest first
+    fn releases(&self, tag_pattern: Option<&str>) -> Result<Vec<(Release, Oid)>> {
+        let pattern = match tag_pattern {
+            Some(glob) => Some(patterns::glob_to_regex(glob).map_err(|e| {
+                DependencyBlameError::Other(format!(
+                    "Invalid release tag pattern '{}': {}",
+                    glob, e
+                ))
+            })?),
+            None => None,
+        };
+
+        let mut releases = Vec::new();
+        for name in self.repo.tag_names(None)?.iter().flatten() {
+            if pattern.as_ref().is_some_and(|p| !p.is_match(name)) {
+                continue;
+            }
+            // Tags of trees or blobs do not mark releases
+            let commit = match self
+                .repo
+                .revparse_single(&format!("refs/tags/{}", name))
+                .and_then(|object| object.peel_to_commit())
+            {
+                Ok(commit) => commit,
+                Err(_) => continue,
+            };
+            let date =
+                DateTime::from_timestamp(commit.time().seconds(), 0).unwrap_or_else(Utc::now);
+            releases.push((
+                Release {
+                    tag: name.to_string(),
+                    commit_hash: commit.id().to_string(),
+                    date,
+                },
+                commit.id(),
+            ));
+        }
+
+        releases.sort_by(|(a, _), (b, _)| a.date.cmp(&b.date).then_with(|| a.tag.cmp(&b.tag)));
+        Ok(releases)
+    }
+
+    /// Blame the dependency's entry in the manifest of the analyzed revision.
+    ///
+    /// Finds the commit that last changed any line of the entry, and follows
+    /// the entry's first line back through earlier edits, moves within the
+    /// file and renames of the file to the commit that introduced it.
+    /// Returns `None` when the committed manifest has no entry for it.
+    pub fn blame_entry(
+        &self,
+        dependency_file: &Path,
+        dependency_name: &str,
+    ) -> Result<Option<EntryBlame>> {
+        let relative_path = self.relative_path(dependency_file)?;
+        let start = self.start_commit()?;
+        let entry = EntryPattern::new(dependency_name);
+
+        let (start_line, end_line) = match self.entry_lines_at(start, &relative_path, &entry)? {
+            Some(range) => range,
+            None => return Ok(None),
+        };
+
+        let blame = self.blame_lines(start, &relative_path, start_line, end_line)?;
+        let mut origins = Vec::new();
+        for line in start_line..=end_line {
+            if let Some(origin) = line_origin(&blame, line, &relative_path) {
+                origins.push(origin);
+            }
+        }
+        let mut last_changed: Option<(i64, &LineOrigin)> = None;
+        for origin in &origins {
+            let time = self.repo.find_commit(origin.commit)?.time().seconds();
+            if last_changed.is_none_or(|(newest, _)| time > newest) {
+                last_changed = Some((time, origin));
+            }
+        }
+        let last_changed = match last_changed {
+            Some((_, origin)) => self.origin_info(origin)?,
+            None => return Ok(None),
+        };
+
+        // Blame the same entry in the parent of the commit that last changed
+        // its first line, until a parent has no such entry
+        let mut introduced = origins.swap_remove(0);
+        loop {
+            let commit = self.repo.find_commit(introduced.commit)?;
+            let parent = match commit.parent_ids().next() {
+                Some(parent) => parent,
+                None => break,
+            };
+            // Follow the line to the file it was renamed or copied from
+            let path =
+                if blob_at(&self.repo.find_commit(parent)?.tree()?, &introduced.path).is_some() {
+                    introduced.path.clone()
+                } else {
+                    match self.renamed_from(&commit, &introduced.path, true)? {
+                        Some(old_path) => old_path,
+                        None => break,
+                    }
+                };
+            let line = match self.entry_lines_at(parent, &path, &entry)? {
+                Some((line, _)) => line,
+                None => break,
+            };
+            let blame = self.blame_lines(parent, &path, line, line)?;
+            match line_origin(&blame, line, &path) {
+                Some(origin) => introduced = origin,
+                None => break,
+            }
+        }
+
+        Ok(Some(EntryBlame {
+            start_line,
+            end_line,
+            last_changed,
+            introduced: self.origin_info(&introduced)?,
+        }))
+    }
+
+    /// Lines of the entry `pattern` finds in a file as of `commit`
+    fn entry_lines_at(
+        &self,
+        commit: Oid,
+        relative_path: &Path,
+        pattern: &EntryPattern,
+    ) -> Result<Option<(usize, usize)>> {
+        let tree = self.repo.find_commit(commit)?.tree()?;
+        let entry = match tree.get_path(relative_path) {
+            Ok(entry) if entry.kind() == Some(ObjectType::Blob) => entry,
+            _ => return Ok(None),
+        };
+        let blob = self.repo.find_blob(entry.id())?;
+        Ok(pattern.lines(&String::from_utf8_lossy(blob.content())))
+    }
+
+    /// Blame a line range of a file as of `commit`, letting blame follow
+    /// lines moved or copied from other files
+    fn blame_lines(
+        &self,
+        commit: Oid,
+        relative_path: &Path,
+        start_line: usize,
+        end_line: usize,
+    ) -> Result<Blame<'_>> {
+        let mut options = BlameOptions::new();
+        options
+            .newest_commit(commit)
+            .use_mailmap(true)
+            .track_copies_same_commit_moves(true)
+            .track_copies_same_commit_copies(true)
+            .min_line(start_line)
+            .max_line(end_line);
+        Ok(self.repo.blame_file(relative_path, Some(&mut options))?)
+    }
+
+    fn origin_info(&self, origin: &LineOrigin) -> Result<GitInfo> {
+        let commit = self.repo.find_commit(origin.commit)?;
+        let mut info = self.extract_commit_info(&commit, &origin.path)?;
+        info.line_number = Some(origin.line);
+        Ok(info)
+    }
+
+    /// Collect the commits in `from..to` that changed any of `files`
+    /// (relative to the repository root), oldest first, with the hunks they
+    /// changed. Merge commits are skipped so changes are attributed to the
+    /// commits that made them.
+    pub fn changes_in_range(
+        &self,
+        from: Oid,
+        to: Oid,
+        files: &[PathBuf],
+    ) -> Result<Vec<CommitChanges>> {
         let mut revwalk = self.repo.revwalk()?;
-        revwalk.push_head()?;
-        revwalk.simplify_first_parent()?;
+        revwalk.push(to)?;
+        revwalk.hide(from)?;
+        revwalk.set_sorting(git2::Sort::TOPOLOGICAL | git2::Sort::TIME | git2::Sort::REVERSE)?;
 
-        let mut history = Vec::new();
+        let mut changes = Vec::new();
 
         for oid_result in revwalk {
-            let oid = oid_result?;
-            let commit = self.repo.find_commit(oid)?;
+            let commit = self.repo.find_commit(oid_result?)?;
+            if commit.parent_count() > 1 {
+                continue;
+            }
+
+            let tree = commit.tree()?;
+            let parent_tree = match commit.parent_count() {
+                0 => None,
+                _ => Some(commit.parent(0)?.tree()?),
+            };
+
+            let mut diff_opts = DiffOptions::new();
+            diff_opts.context_lines(1);
+            for file in files {
+                diff_opts.pathspec(file);
+            }
+            let diff = self.repo.diff_tree_to_tree(
+                parent_tree.as_ref(),
+                Some(&tree),
+                Some(&mut diff_opts),
+            )?;
+
+            let mut hunks: Vec<DiffHunk> = Vec::new();
+            diff.print(git2::DiffFormat::Patch, |delta, _, line| {
+                let text = String::from_utf8_lossy(line.content());
+                match line.origin() {
+                    'H' => hunks.push(DiffHunk {
+                        file: delta
+                            .new_file()
+                            .path()
+                            .unwrap_or(Path::new(""))
+                            .to_path_buf(),
+                        changed: String::new(),
+                        text: String::new(),
+                    }),
+                    origin @ ('+' | '-' | ' ') => {
+                        if let Some(hunk) = hunks.last_mut() {
+                            if origin != ' ' {
+                                hunk.changed.push_str(&text);
+                            }
+                            hunk.text.push_str(&text);
+                        }
+                    }
+                    _ => {}
+                }
+                true
+            })?;
+
+            if let Some(first) = hunks.first() {
+                let info = self.extract_commit_info(&commit, &first.file)?;
+                changes.push(CommitChanges { info, hunks });
+            }
+        }
+
+        Ok(changes)
+    }
+
+    /// The analyzed revision, HEAD by default
+    fn start_commit(&self) -> Result<Oid> {
+        match &self.revision {
+            Some((commit, _)) => Ok(*commit),
+            None => Ok(self.repo.head()?.peel_to_commit()?.id()),
+        }
+    }
+
+    fn workdir(&self) -> Result<&Path> {
+        self.repo.workdir().ok_or_else(|| {
+            DependencyBlameError::Other("Repository has no working directory".to_string())
+        })
+    }
+
+    /// Convert a path on disk into a path relative to the repository root.
+    /// Both sides are canonicalized so relative inputs like `./Cargo.toml` work.
+    fn relative_path(&self, file_path: &Path) -> Result<PathBuf> {
+        if let Some((_, snapshot_dir)) = &self.revision {
+            return Ok(file_path
+                .strip_prefix(snapshot_dir)
+                .unwrap_or(file_path)
+                .to_path_buf());
+        }
+
+        let repo_path = self.workdir()?;
+        // Files that no longer exist, such as deleted manifests, cannot be
+        // canonicalized
+        if let Ok(relative) = file_path.strip_prefix(repo_path) {
+            return Ok(relative.to_path_buf());
+        }
+
+        let repo_path = repo_path
+            .canonicalize()
+            .unwrap_or_else(|_| repo_path.to_path_buf());
+        let file_path = file_path
+            .canonicalize()
+            .unwrap_or_else(|_| file_path.to_path_buf());
+
+        Ok(file_path
+            .strip_prefix(&repo_path)
+            .map(Path::to_path_buf)
+            .unwrap_or(file_path))
+    }
+
+    /// Whether a dependency declared in the working directory's manifest was
+    /// added there without being committed: staged when the index declares
+    /// it as well, unstaged otherwise. `None` when HEAD declares it, or when
+    /// a revision is analyzed instead of the working directory.
+    pub fn local_change(
+        &self,
+        dependency_file: &Path,
+        dependency_name: &str,
+        parser: &dyn DependencyParser,
+    ) -> Result<Option<LocalChange>> {
+        if self.revision.is_some() {
+            return Ok(None);
+        }
+        let relative_path = self.relative_path(dependency_file)?;
+        let file_name = relative_path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or_default();
+
+        let declares = |blob: Option<Oid>| -> Result<bool> {
+            let blob = match blob {
+                Some(blob) => blob,
+                None => return Ok(false),
+            };
+            let path = self.blob_file(blob, file_name)?;
+            Ok(match parser.parse_dependencies(&path) {
+                Ok(deps) => deps.iter().any(|d| d.name == dependency_name),
+                // An older revision may not parse; the entry can still be found
+                Err(_) => {
+                    manifest::entry_lines(&fs::read_to_string(&path)?, dependency_name).is_some()
+                }
+            })
+        };
+
+        if declares(self.head_blob(&relative_path))? {
+            return Ok(None);
+        }
+        let state = if declares(self.index_blob(&relative_path)?)? {
+            LocalState::Staged
+        } else {
+            LocalState::Unstaged
+        };
+
+        Ok(Some(LocalChange {
+            state,
+            author: self.current_author(),
+        }))
+    }
+
+    /// Dependencies declared by HEAD's version of a manifest and by the
+    /// version staged in the index, in that order. A version that is
+    /// missing or does not parse declares none.
+    pub fn staged_dependencies(
+        &self,
+        dependency_file: &Path,
+        parser: &dyn DependencyParser,
+    ) -> Result<(Vec<Dependency>, Vec<Dependency>)> {
+        let relative_path = self.relative_path(dependency_file)?;
+        let file_name = relative_path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or_default();
+
+        let declared = |blob: Option<Oid>| -> Result<Vec<Dependency>> {
+            match blob {
+                Some(blob) => Ok(parser
+                    .parse_dependencies(&self.blob_file(blob, file_name)?)
+                    .unwrap_or_default()),
+                None => Ok(Vec::new()),
+            }
+        };
+
+        Ok((
+            declared(self.head_blob(&relative_path))?,
+            declared(self.index_blob(&relative_path)?)?,
+        ))
+    }
+
+    /// The file's blob in HEAD; `None` as well when there are no commits yet
+    fn head_blob(&self, relative_path: &Path) -> Option<Oid> {
+        let tree = self.repo.head().and_then(|head| head.peel_to_tree()).ok()?;
+        blob_at(&tree, relative_path)
+    }
+
+    /// The file's blob staged in the index
+    fn index_blob(&self, relative_path: &Path) -> Result<Option<Oid>> {
+        Ok(self
+            .repo
+            .index()?
+            .get_path(relative_path, 0)
+            .map(|entry| entry.id))
+    }
+
+    /// The reason recorded for a dependency in the dependency-blame note
+    /// on a commit, see [`rationale::NOTES_REF`]
+    pub fn note_reason(
+        &self,
+        commit_hash: &str,
+        ecosystem: EcosystemType,
+        dependency_name: &str,
+    ) -> Result<Option<String>> {
+        let note = match self
+            .repo
+            .find_note(Some(rationale::NOTES_REF), Oid::from_str(commit_hash)?)
+        {
+            Ok(note) => note,
+            Err(_) => return Ok(None),
+        };
+        // A note that is not in the reasons format was not written by us
+        Ok(note
+            .message()
+            .and_then(|message| rationale::parse_reasons(message).ok())
+            .and_then(|reasons| reasons.get(ecosystem, dependency_name).map(String::from)))
+    }
+
+    /// Record the reason for a dependency in the dependency-blame note on a
+    /// commit, keeping the reasons of other dependencies it introduced.
+    /// With an `ecosystem`, it only applies to that ecosystem's dependency.
+    pub fn set_note_reason(
+        &self,
+        commit_hash: &str,
+        ecosystem: Option<EcosystemType>,
+        dependency_name: &str,
+        reason: &str,
+    ) -> Result<()> {
+        let commit = Oid::from_str(commit_hash)?;
+        let existing = self
+            .repo
+            .find_note(Some(rationale::NOTES_REF), commit)
+            .ok()
+            .and_then(|note| note.message().map(str::to_string))
+            .filter(|message| rationale::parse_reasons(message).is_ok())
+            .unwrap_or_default();
+        let content = rationale::set_reason(&existing, ecosystem, dependency_name, reason);
+
+        let signature = self.repo.signature()?;
+        self.repo.note(
+            &signature,
+            &signature,
+            Some(rationale::NOTES_REF),
+            commit,
+            &content,
+            true,
+        )?;
+        Ok(())
+    }
+
+    /// The identity commits would be made with, from the git config
+    fn current_author(&self) -> Option<String> {
+        let signature = self.repo.signature().ok()?;
+        let signature = match &self.mailmap {
+            Some(mailmap) => mailmap.resolve_signature(&signature).ok()?,
+            None => signature,
+        };
+        Some(identity(&signature))
+    }
+
+    /// How much history a shallow clone has, from the analyzed commit back
+    /// to where it ends; `None` for a complete clone
+    pub fn shallow_history(&self) -> Result<Option<ShallowHistory>> {
+        if self.shallow.is_empty() {
+            return Ok(None);
+        }
 
-            // Check if this commit modified the dependency file
-            if self.commit_modified_file(&commit, relative_path)? {
-                let git_info = self.extract_commit_info(&commit, relative_path)?;
-                history.push(git_info);
+        let mut revwalk = self.repo.revwalk()?;
+        revwalk.push(self.start_commit()?)?;
+        let mut depth = 0;
+        let mut boundaries = Vec::new();
+        for oid_result in revwalk {
+            let oid = oid_result?;
+            depth += 1;
+            if self.shallow.contains(&oid) {
+                boundaries.push(oid.to_string());
             }
         }
 
-        Ok(history)
+        Ok(Some(ShallowHistory { boundaries, depth }))
     }
 
     /// Extract GitInfo from a commit
     fn extract_commit_info(&self, commit: &git2::Commit, file_path: &Path) -> Result<GitInfo> {
-        let author = commit.author();
+        // The canonical identity from .mailmap, if the repository has one
+        let author = self
+            .mailmap
+            .as_ref()
+            .and_then(|mailmap| commit.author_with_mailmap(mailmap).ok())
+            .unwrap_or_else(|| commit.author().to_owned());
         let time = commit.time();
         let timestamp = DateTime::from_timestamp(time.seconds(), 0).unwrap_or_else(Utc::now);
 
+        let message = commit.message().unwrap_or("No commit message").to_string();
+
         Ok(GitInfo {
             commit_hash: commit.id().to_string(),
-            author: format!(
-                "{} <{}>",
-                author.name().unwrap_or("Unknown"),
-                author.email().unwrap_or("unknown@unknown.com")
-            ),
+            author: identity(&author),
             date: timestamp,
-            message: commit.message().unwrap_or("No commit message").to_string(),
             file_path: file_path.to_path_buf(),
             line_number: None,
+            merged_in: None,
+            pull_request: merge::parse_pull_request(&message),
+            conventional: commit_message::conventional_commit(&message),
+            issues: commit_message::issue_references(&message, &self.issue_patterns),
+            trailers: commit_message::trailers(&message),
+            reason: None,
+            at_boundary: self.shallow.contains(&commit.id()),
+            message,
         })
     }
+}
 
-    /// Check if a commit modified a specific file
-    fn commit_modified_file(&self, commit: &git2::Commit, file_path: &Path) -> Result<bool> {
-        let tree = commit.tree()?;
+/// The commit, path and line a blamed line came from
+fn line_origin(blame: &Blame, line: usize, relative_path: &Path) -> Option<LineOrigin> {
+    let hunk = blame.get_line(line)?;
+    let offset = line.checked_sub(hunk.final_start_line())?;
+    Some(LineOrigin {
+        commit: hunk.orig_commit_id(),
+        path: hunk.path().unwrap_or(relative_path).to_path_buf(),
+        line: hunk.orig_start_line() + offset,
+    })
+}
 
-        // For the first commit (no parent), check if file exists
-        if commit.parent_count() == 0 {
-            return Ok(tree.get_path(file_path).is_ok());
-        }
+/// `Name <email>` of a signature
+fn identity(signature: &Signature) -> String {
+    format!(
+        "{} <{}>",
+        signature.name().unwrap_or("Unknown"),
+        signature.email().unwrap_or("unknown@unknown.com")
+    )
+}
+
+/// The commits listed in `.git/shallow`, where a shallow clone's history is cut off
+fn shallow_boundaries(repo: &Repository) -> Vec<Oid> {
+    if !repo.is_shallow() {
+        return Vec::new();
+    }
+    fs::read_to_string(repo.path().join("shallow"))
+        .map(|content| {
+            content
+                .lines()
+                .filter_map(|line| Oid::from_str(line.trim()).ok())
+                .collect()
+        })
+        .unwrap_or_default()
+}
 
-        // For subsequent commits, check diff with parent
-        let parent = commit.parent(0)?;
-        let parent_tree = parent.tree()?;
+/// The blob at `relative_path` in a tree, if it is a file
+fn blob_at(tree: &Tree, relative_path: &Path) -> Option<Oid> {
+    tree.get_path(relative_path)
+        .ok()
+        .filter(|entry| entry.kind() == Some(ObjectType::Blob))
+        .map(|entry| entry.id())
+}
 
-        let mut diff_opts = DiffOptions::new();
-        diff_opts.pathspec(file_path);
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::cache;
+    use crate::ecosystems::rust::RustAdapter;
+    use crate::ecosystems::traits::EcosystemAdapter;
 
-        let diff =
-            self.repo
-                .diff_tree_to_tree(Some(&parent_tree), Some(&tree), Some(&mut diff_opts))?;
+    fn stage(repo: &Repository, path: &Path, content: &str) -> Oid {
+        fs::write(repo.workdir().unwrap().join(path), content).unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(path).unwrap();
+        index.write().unwrap();
+        index.write_tree().unwrap()
+    }
+
+    fn commit(repo: &Repository, path: &Path, content: &str, message: &str) -> Oid {
+        let tree = repo.find_tree(stage(repo, path, content)).unwrap();
+        let parent = repo.head().ok().and_then(|head| head.peel_to_commit().ok());
+        let signature = Signature::now("A", "a@example.com").unwrap();
+        repo.commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            message,
+            &tree,
+            &parent.iter().collect::<Vec<_>>(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn staged_dependencies_leave_the_git_directory_alone() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        let manifest = Path::new("Cargo.toml");
+        commit(
+            &repo,
+            manifest,
+            "[dependencies]\nserde = \"1\"\n",
+            "Add serde",
+        );
+        stage(
+            &repo,
+            manifest,
+            "[dependencies]\nserde = \"1\"\nlog = \"0.4\"\n",
+        );
+
+        let git_analyzer = GitAnalyzer::new(dir.path()).unwrap();
+        let (head, staged) = git_analyzer
+            .staged_dependencies(&dir.path().join(manifest), RustAdapter::new().parser())
+            .unwrap();
+        let names = |deps: &[Dependency]| deps.iter().map(|d| d.name.clone()).collect::<Vec<_>>();
+        assert_eq!(names(&head), ["serde"]);
+        assert_eq!(names(&staged), ["log", "serde"]);
+        assert!(!cache::cache_dir(&repo).exists());
+    }
+
+    #[test]
+    fn blame_follows_an_entry_through_a_reformat() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        let manifest = Path::new("Cargo.toml");
+        let added = commit(
+            &repo,
+            manifest,
+            "[dependencies]\nlog = \"0.4\"\nserde = \"1\"\n",
+            "Add serde",
+        );
+        let reformatted = commit(
+            &repo,
+            manifest,
+            "[dependencies]\nlog = \"0.4\"\nserde = { version = \"1\", features = [\n    \"derive\",\n] }\n",
+            "Enable serde derive",
+        );
+
+        let git_analyzer = GitAnalyzer::new(dir.path()).unwrap();
+        let blame = git_analyzer
+            .blame_entry(&dir.path().join(manifest), "serde")
+            .unwrap()
+            .unwrap();
+        assert_eq!((blame.start_line, blame.end_line), (3, 5));
+        assert_eq!(blame.last_changed.commit_hash, reformatted.to_string());
+        assert_eq!(blame.introduced.commit_hash, added.to_string());
+        assert_eq!(blame.introduced.line_number, Some(3));
+        assert!(git_analyzer
+            .blame_entry(&dir.path().join(manifest), "anyhow")
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn deleted_files_leave_out_renamed_and_restored_ones() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        let manifest =
+            |name: &str| format!("[package]\nname = \"{name}\"\n\n[dependencies]\nserde = \"1\"\n");
+        for member in ["removed", "renamed", "restored"] {
+            fs::create_dir_all(dir.path().join(member)).unwrap();
+            stage(
+                &repo,
+                &Path::new(member).join("Cargo.toml"),
+                &manifest(member),
+            );
+        }
+        commit(&repo, Path::new("README.md"), "", "Add members");
+
+        fs::create_dir_all(dir.path().join("moved")).unwrap();
+        fs::rename(
+            dir.path().join("renamed/Cargo.toml"),
+            dir.path().join("moved/Cargo.toml"),
+        )
+        .unwrap();
+        let mut index = repo.index().unwrap();
+        for member in ["removed", "renamed", "restored"] {
+            index
+                .remove_path(&Path::new(member).join("Cargo.toml"))
+                .unwrap();
+        }
+        index.add_path(Path::new("moved/Cargo.toml")).unwrap();
+        index.write().unwrap();
+        commit(&repo, Path::new("README.md"), "Members\n", "Reorganize");
+        commit(
+            &repo,
+            Path::new("restored/Cargo.toml"),
+            &manifest("restored"),
+            "Restore a member",
+        );
 
-        Ok(diff.deltas().len() > 0)
+        let git_analyzer = GitAnalyzer::new(dir.path()).unwrap();
+        let deleted = git_analyzer
+            .deleted_files(|path| path.ends_with("Cargo.toml"))
+            .unwrap();
+        let workdir = repo.workdir().unwrap();
+        assert_eq!(deleted, vec![workdir.join("removed/Cargo.toml")]);
+        assert_eq!(
+            git_analyzer.relative_path(&deleted[0]).unwrap(),
+            Path::new("removed/Cargo.toml")
+        );
     }
 }
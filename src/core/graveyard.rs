@@ -0,0 +1,184 @@
+use crate::core::dependency::{Dependency, DependencyType, EcosystemType, GitInfo};
+use crate::core::error::Result;
+use crate::core::git_analyzer::GitAnalyzer;
+use crate::ecosystems::traits::DependencyParser;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// A dependency that was declared in a manifest and later removed from it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemovedDependency {
+    pub name: String,
+    /// Last declared version
+    pub version: String,
+    pub ecosystem: EcosystemType,
+    pub dependency_type: DependencyType,
+    /// Manifest it was removed from, relative to the repository root
+    pub manifest: PathBuf,
+    pub added: GitInfo,
+    pub removed: GitInfo,
+    /// Days between the commit that added it and the one that removed it
+    pub lifetime_days: i64,
+}
+
+/// Find the dependencies that were removed from a manifest over its history
+/// and are not declared in it any more. A dependency removed several times
+/// is reported with its last removal.
+///
+/// Each revision of the manifest is parsed; revisions that fail to parse
+/// are skipped, as if the manifest had not changed.
+pub fn removed_dependencies(
+    git_analyzer: &GitAnalyzer,
+    parser: &dyn DependencyParser,
+    manifest: &Path,
+    relative_manifest: &Path,
+) -> Result<Vec<RemovedDependency>> {
+    let file_name = manifest
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or_default();
+
+    // Dependencies currently declared, with the commit that added them
+    let mut alive: HashMap<String, (Dependency, GitInfo)> = HashMap::new();
+    let mut removed: HashMap<String, RemovedDependency> = HashMap::new();
+
+    for (commit, blob) in git_analyzer.file_revisions(manifest)? {
+        let declared = match blob {
+            Some(blob) => {
+                let path = git_analyzer.blob_file(blob, file_name)?;
+                match parser.parse_dependencies(&path) {
+                    Ok(deps) => deps,
+                    Err(_) => continue,
+                }
+            }
+            None => Vec::new(),
+        };
+
+        let mut current: HashMap<String, Dependency> = HashMap::new();
+        for dep in declared {
+            current.entry(dep.name.clone()).or_insert(dep);
+        }
+
+        let gone: Vec<String> = alive
+            .keys()
+            .filter(|name| !current.contains_key(*name))
+            .cloned()
+            .collect();
+        for name in gone {
+            if let Some((dep, mut added)) = alive.remove(&name) {
+                let lifetime_days = (commit.date - added.date).num_days();
+                let mut removed_in = commit.clone();
+                added.explain(&dep.name);
+                removed_in.explain(&dep.name);
+                removed.insert(
+                    name,
+                    RemovedDependency {
+                        name: dep.name,
+                        version: dep.version,
+                        ecosystem: dep.ecosystem,
+                        dependency_type: dep.dependency_type,
+                        manifest: relative_manifest.to_path_buf(),
+                        added,
+                        removed: removed_in,
+                        lifetime_days,
+                    },
+                );
+            }
+        }
+
+        for (name, dep) in current {
+            match alive.get_mut(&name) {
+                // Keep the last declared version and type
+                Some((known, _)) => *known = dep,
+                None => {
+                    removed.remove(&name);
+                    alive.insert(name, (dep, commit.clone()));
+                }
+            }
+        }
+    }
+
+    let mut removed: Vec<RemovedDependency> = removed.into_values().collect();
+    removed.sort_by(|a, b| {
+        b.removed
+            .date
+            .cmp(&a.removed.date)
+            .then(a.name.cmp(&b.name))
+    });
+    Ok(removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ecosystems::rust::RustAdapter;
+    use crate::ecosystems::traits::EcosystemAdapter;
+    use git2::{Oid, Repository, Signature};
+    use std::fs;
+
+    fn commit(repo: &Repository, content: &str, message: &str) -> Oid {
+        fs::write(repo.workdir().unwrap().join("Cargo.toml"), content).unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new("Cargo.toml")).unwrap();
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let parent = repo.head().ok().and_then(|head| head.peel_to_commit().ok());
+        let signature = Signature::now("A", "a@example.com").unwrap();
+        repo.commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            message,
+            &tree,
+            &parent.iter().collect::<Vec<_>>(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn dependencies_are_reported_with_their_last_removal() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        commit(
+            &repo,
+            "[dependencies]\nanyhow = \"1\"\nlog = \"0.4\"\nserde = \"1\"\n",
+            "Add dependencies",
+        );
+        commit(
+            &repo,
+            "[dependencies]\nserde = \"1\"\n",
+            "Drop anyhow and log",
+        );
+        let readded = commit(
+            &repo,
+            "[dependencies]\nanyhow = \"1.0.80\"\nlog = \"0.4\"\nserde = \"1\"\n",
+            "Bring anyhow and log back",
+        );
+        // Unparsable, so it neither removes nor re-adds anything
+        commit(&repo, "[dependencies\n", "Break the manifest");
+        let removed = commit(
+            &repo,
+            "[dependencies]\nlog = \"0.4\"\nserde = \"1\"\n",
+            "Drop anyhow",
+        );
+
+        let git_analyzer = GitAnalyzer::new(dir.path()).unwrap();
+        let adapter = RustAdapter::new();
+        let found = removed_dependencies(
+            &git_analyzer,
+            adapter.parser(),
+            &dir.path().join("Cargo.toml"),
+            Path::new("Cargo.toml"),
+        )
+        .unwrap();
+
+        assert_eq!(found.len(), 1);
+        let anyhow = &found[0];
+        assert_eq!(anyhow.name, "anyhow");
+        assert_eq!(anyhow.version, "1.0.80");
+        assert_eq!(anyhow.manifest, Path::new("Cargo.toml"));
+        assert_eq!(anyhow.added.commit_hash, readded.to_string());
+        assert_eq!(anyhow.removed.commit_hash, removed.to_string());
+    }
+}
@@ -0,0 +1,213 @@
+use crate::core::commit_message;
+use crate::core::dependency::Dependency;
+use crate::core::error::{DependencyBlameError, Result};
+use git2::Repository;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// First line after the shebang of the hook scripts `install` writes, so
+/// they can be told apart from hooks written by hand or by other tools
+const HOOK_MARKER: &str = "# Installed by dependency-blame install-hooks";
+
+/// Trailer that gives the reason for new dependencies
+pub const REASON_TRAILER: &str = "Dependency-Reason";
+
+/// The hooks `install` writes, with the command each one runs
+const HOOKS: [(&str, &str); 2] = [
+    ("pre-commit", "dependency-blame hook pre-commit"),
+    ("commit-msg", "dependency-blame hook commit-msg \"$1\""),
+];
+
+/// Write the pre-commit and commit-msg hooks into the repository's hooks
+/// directory (`core.hooksPath`, or `.git/hooks`). Hooks that were not
+/// written by this command are only replaced with `force`.
+pub fn install(repo_path: &Path, force: bool) -> Result<Vec<PathBuf>> {
+    let repo = Repository::discover(repo_path)
+        .map_err(|_| DependencyBlameError::GitRepoNotFound(repo_path.to_path_buf()))?;
+    let hooks_dir = match repo.config()?.get_path("core.hooksPath") {
+        // A relative hooksPath is relative to the working directory
+        Ok(path) => repo.workdir().unwrap_or_else(|| repo.path()).join(path),
+        Err(_) => repo.path().join("hooks"),
+    };
+    fs::create_dir_all(&hooks_dir)?;
+
+    let mut installed = Vec::new();
+    for (name, command) in HOOKS {
+        let path = hooks_dir.join(name);
+        if !force {
+            if let Ok(existing) = fs::read_to_string(&path) {
+                if !existing.contains(HOOK_MARKER) {
+                    return Err(DependencyBlameError::Other(format!(
+                        "{} already exists; use --force to replace it",
+                        path.display()
+                    )));
+                }
+            }
+        }
+        fs::write(
+            &path,
+            format!("#!/bin/sh\n{}\nexec {}\n", HOOK_MARKER, command),
+        )?;
+        make_executable(&path)?;
+        installed.push(path);
+    }
+    Ok(installed)
+}
+
+#[cfg(unix)]
+fn make_executable(path: &Path) -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    fs::set_permissions(path, fs::Permissions::from_mode(0o755))?;
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn make_executable(_path: &Path) -> Result<()> {
+    Ok(())
+}
+
+/// Read the message git passes to the commit-msg hook, as it will be
+/// committed: without comment lines (`core.commentChar`, `#` by default)
+/// and without the diff below the scissors line of `git commit --verbose`
+pub fn read_commit_message(repo_path: &Path, message_file: &Path) -> Result<String> {
+    let comment_char = Repository::discover(repo_path)
+        .ok()
+        .and_then(|repo| repo.config().ok()?.get_string("core.commentChar").ok())
+        .and_then(|value| value.chars().next())
+        .filter(|c| !c.is_alphanumeric())
+        .unwrap_or('#');
+    let content = fs::read_to_string(message_file)?;
+
+    let mut message = String::new();
+    for line in content.lines() {
+        if line.starts_with(comment_char) {
+            if line.contains(">8") {
+                break;
+            }
+            continue;
+        }
+        message.push_str(line);
+        message.push('\n');
+    }
+    Ok(message)
+}
+
+/// The added dependencies the commit message gives no reason for.
+///
+/// A dependency is explained when the message mentions it by name. A
+/// `Dependency-Reason` trailer that names none of the added dependencies
+/// explains all of them.
+pub fn unexplained<'a>(message: &str, added: &'a [Dependency]) -> Vec<&'a Dependency> {
+    let blanket_reason = commit_message::trailers(message)
+        .iter()
+        .filter(|trailer| trailer.key.eq_ignore_ascii_case(REASON_TRAILER))
+        .any(|trailer| {
+            !added
+                .iter()
+                .any(|dep| commit_message::mentions(&trailer.value, &dep.name))
+        });
+    if blanket_reason {
+        return Vec::new();
+    }
+
+    added
+        .iter()
+        .filter(|dep| !commit_message::mentions(message, &dep.name))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::dependency::{DependencyType, EcosystemType};
+
+    fn dependency(name: &str) -> Dependency {
+        Dependency::new(
+            name.to_string(),
+            "1".to_string(),
+            EcosystemType::Rust,
+            DependencyType::Direct,
+        )
+    }
+
+    #[test]
+    fn commit_message_without_comments_and_diff() {
+        let dir = tempfile::tempdir().unwrap();
+        Repository::init(dir.path()).unwrap();
+        let message_file = dir.path().join("COMMIT_EDITMSG");
+        fs::write(
+            &message_file,
+            "Add serde\n\nNeeded to read the config.\n# Please enter the commit message for your changes.\n# ------------------------ >8 ------------------------\n# Do not modify or remove the line above.\ndiff --git a/Cargo.toml b/Cargo.toml\n+anyhow = \"1\"\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            read_commit_message(dir.path(), &message_file).unwrap(),
+            "Add serde\n\nNeeded to read the config.\n"
+        );
+    }
+
+    #[test]
+    fn commit_message_with_a_custom_comment_char() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        repo.config()
+            .unwrap()
+            .set_str("core.commentChar", ";")
+            .unwrap();
+        let message_file = dir.path().join("COMMIT_EDITMSG");
+        fs::write(
+            &message_file,
+            "Add serde\n#42 asked for it\n; Please enter the commit message\n; ------------------------ >8 ------------------------\n+serde = \"1\"\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            read_commit_message(dir.path(), &message_file).unwrap(),
+            "Add serde\n#42 asked for it\n"
+        );
+    }
+
+    #[test]
+    fn dependencies_explained_by_name_or_trailer() {
+        let added = [dependency("serde"), dependency("anyhow")];
+        let names = |message: &str| -> Vec<String> {
+            unexplained(message, &added)
+                .into_iter()
+                .map(|d| d.name.clone())
+                .collect()
+        };
+
+        assert_eq!(names("Read the config with serde\n"), ["anyhow"]);
+        assert!(names("Read the config\n\nDependency-Reason: parsing and errors\n").is_empty());
+        // A trailer naming one of them only explains that one
+        assert_eq!(
+            names("Read the config\n\nDependency-Reason: serde parses it\n"),
+            ["anyhow"]
+        );
+    }
+
+    #[test]
+    fn foreign_hooks_are_only_replaced_with_force() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        let hooks_dir = repo.path().join("hooks");
+        fs::create_dir_all(&hooks_dir).unwrap();
+        let foreign = "#!/bin/sh\nnpx lint-staged\n";
+        fs::write(hooks_dir.join("pre-commit"), foreign).unwrap();
+
+        assert!(install(dir.path(), false).is_err());
+        assert_eq!(
+            fs::read_to_string(hooks_dir.join("pre-commit")).unwrap(),
+            foreign
+        );
+
+        let installed = install(dir.path(), true).unwrap();
+        assert_eq!(installed.len(), 2);
+        assert!(fs::read_to_string(hooks_dir.join("pre-commit"))
+            .unwrap()
+            .contains(HOOK_MARKER));
+        // Its own hooks are replaced without asking
+        install(dir.path(), false).unwrap();
+    }
+}
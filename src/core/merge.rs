@@ -0,0 +1,216 @@
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+
+/// A pull request, merge request or merged branch, parsed from a commit message
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PullRequest {
+    /// `#123` on GitHub and Bitbucket, `!123` on GitLab; `None` for a plain branch merge
+    pub number: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// Source branch, when the message names it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub branch: Option<String>,
+}
+
+/// Parse the pull request a commit message refers to, from the merge commit
+/// formats of GitHub (`Merge pull request #123 from owner/branch`), GitLab
+/// (`Merge branch 'x' into 'main'` with `See merge request group/project!456`)
+/// and Bitbucket Cloud and Server, GitHub's squash merges (`Title (#123)`)
+/// and plain `git merge` messages.
+pub fn parse_pull_request(message: &str) -> Option<PullRequest> {
+    let MergePatterns {
+        github_merge,
+        github_squash,
+        gitlab_merge,
+        gitlab_reference,
+        bitbucket_cloud,
+        bitbucket_server,
+        bitbucket_server_branch,
+        git_merge,
+    } = merge_patterns();
+
+    let subject = message.lines().next().unwrap_or("").trim();
+    let body_title = message
+        .lines()
+        .skip(1)
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .map(str::to_string);
+    let number = |text: &str| text.parse().ok();
+
+    if let Some(captures) = github_merge.captures(subject) {
+        return Some(PullRequest {
+            number: number(&captures[1]),
+            title: body_title,
+            branch: Some(captures[2].to_string()),
+        });
+    }
+
+    if let Some(captures) = bitbucket_cloud.captures(subject) {
+        return Some(PullRequest {
+            number: number(&captures[2]),
+            title: body_title,
+            branch: Some(captures[1].to_string()),
+        });
+    }
+
+    if let Some(captures) = bitbucket_server.captures(subject) {
+        return Some(PullRequest {
+            number: number(&captures[1]),
+            title: Some(captures[2].trim().to_string()),
+            branch: bitbucket_server_branch
+                .captures(message)
+                .map(|c| c[1].to_string()),
+        });
+    }
+
+    if let Some(captures) = gitlab_merge.captures(subject) {
+        let reference = gitlab_reference.captures(message);
+        // Without a merge request reference it is a plain branch merge
+        let title =
+            body_title.filter(|title| reference.is_some() && !gitlab_reference.is_match(title));
+        return Some(PullRequest {
+            number: reference.and_then(|c| number(&c[1])),
+            title,
+            branch: Some(captures[1].to_string()),
+        });
+    }
+
+    if let Some(captures) = git_merge.captures(subject) {
+        return Some(PullRequest {
+            number: None,
+            title: None,
+            branch: Some(captures[1].to_string()),
+        });
+    }
+
+    if let Some(captures) = github_squash.captures(subject) {
+        return Some(PullRequest {
+            number: number(&captures[2]),
+            title: Some(captures[1].to_string()),
+            branch: None,
+        });
+    }
+
+    None
+}
+
+/// The merge commit message formats [`parse_pull_request`] recognizes
+struct MergePatterns {
+    github_merge: Regex,
+    github_squash: Regex,
+    gitlab_merge: Regex,
+    gitlab_reference: Regex,
+    bitbucket_cloud: Regex,
+    bitbucket_server: Regex,
+    bitbucket_server_branch: Regex,
+    git_merge: Regex,
+}
+
+fn merge_patterns() -> &'static MergePatterns {
+    static PATTERNS: OnceLock<MergePatterns> = OnceLock::new();
+    PATTERNS.get_or_init(|| {
+        let regex = |pattern: &str| Regex::new(pattern).expect("valid regex");
+        MergePatterns {
+            github_merge: regex(r"^Merge pull request #(\d+) from [^/\s]+/(\S+)"),
+            github_squash: regex(r"^(.*\S)\s+\(#(\d+)\)$"),
+            gitlab_merge: regex(r"^Merge branch '([^']+)' into '[^']+'$"),
+            gitlab_reference: regex(r"(?m)^See merge request \S*!(\d+)\s*$"),
+            bitbucket_cloud: regex(r"^Merged in (\S+) \(pull request #(\d+)\)"),
+            bitbucket_server: regex(r"^Pull request #(\d+): (.+)$"),
+            bitbucket_server_branch: regex(r"(?m)^Merge in \S+ from (\S+) to \S+"),
+            git_merge: regex(r"^Merge (?:remote-tracking )?branch '([^']+)'"),
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pull_request(number: Option<u64>, title: Option<&str>, branch: Option<&str>) -> PullRequest {
+        PullRequest {
+            number,
+            title: title.map(str::to_string),
+            branch: branch.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn github_merge_commit() {
+        assert_eq!(
+            parse_pull_request(
+                "Merge pull request #42 from octo/add-serde\n\nParse config with serde"
+            ),
+            Some(pull_request(
+                Some(42),
+                Some("Parse config with serde"),
+                Some("add-serde")
+            ))
+        );
+    }
+
+    #[test]
+    fn github_squash_merge() {
+        assert_eq!(
+            parse_pull_request("Add logging (#7)\n\n* wip"),
+            Some(pull_request(Some(7), Some("Add logging"), None))
+        );
+    }
+
+    #[test]
+    fn gitlab_merge_request() {
+        let message = "Merge branch 'feat/cache' into 'main'\n\nAdd a cache\n\n\
+                       See merge request group/project!15";
+        assert_eq!(
+            parse_pull_request(message),
+            Some(pull_request(
+                Some(15),
+                Some("Add a cache"),
+                Some("feat/cache")
+            ))
+        );
+    }
+
+    #[test]
+    fn gitlab_style_merge_without_request_is_a_branch_merge() {
+        assert_eq!(
+            parse_pull_request("Merge branch 'topic' into 'main'\n\nSome text"),
+            Some(pull_request(None, None, Some("topic")))
+        );
+    }
+
+    #[test]
+    fn bitbucket_cloud_and_server() {
+        assert_eq!(
+            parse_pull_request("Merged in fix-deps (pull request #3)\n\nPin tokio"),
+            Some(pull_request(Some(3), Some("Pin tokio"), Some("fix-deps")))
+        );
+        assert_eq!(
+            parse_pull_request(
+                "Pull request #12: Upgrade React\n\nMerge in PROJ/web from feature/react to master"
+            ),
+            Some(pull_request(
+                Some(12),
+                Some("Upgrade React"),
+                Some("feature/react")
+            ))
+        );
+    }
+
+    #[test]
+    fn plain_git_merge() {
+        assert_eq!(
+            parse_pull_request("Merge remote-tracking branch 'origin/dev'"),
+            Some(pull_request(None, None, Some("origin/dev")))
+        );
+    }
+
+    #[test]
+    fn ordinary_commit_has_no_pull_request() {
+        assert_eq!(parse_pull_request("Add serde (see #7)"), None);
+        assert_eq!(parse_pull_request("Fix #7"), None);
+    }
+}
@@ -1,5 +1,15 @@
+pub mod authors;
+pub mod cache;
+pub mod commit_message;
+pub mod config;
 pub mod dependency;
+pub mod diff;
 pub mod error;
 pub mod git_analyzer;
+pub mod graveyard;
+pub mod hooks;
+pub mod merge;
 pub mod orchestrator;
+pub mod rationale;
+pub mod revision;
 pub mod usage_scanner;
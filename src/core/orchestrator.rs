@@ -1,84 +1,1055 @@
-use crate::core::dependency::{Dependency, DependencyAnalysis, DependencyQuery, UsageInfo};
+use crate::core::authors::{self, AuthorStats, Introduced};
+use crate::core::cache::AnalysisCache;
+use crate::core::config::Config;
+use crate::core::dependency::{
+    Dependency, DependencyAnalysis, DependencyOverride, DependencyQuery, DependencyType,
+    EcosystemType, GitInfo, ImportLocation, UsageInfo,
+};
+use crate::core::diff::{self, DependencyDiff, LockedDependency};
 use crate::core::error::{DependencyBlameError, Result};
 use crate::core::git_analyzer::GitAnalyzer;
+use crate::core::graveyard::{self, RemovedDependency};
+use crate::core::rationale::{self, Rationale, RationaleSource};
+use crate::core::revision::RevisionSnapshot;
 use crate::core::usage_scanner::UsageScanner;
-use crate::ecosystems::registry::EcosystemRegistry;
+use crate::ecosystems::registry::{DetectedManifest, EcosystemRegistry};
+use crate::ecosystems::traits::{EcosystemAdapter, WorkspaceMember};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 pub struct DependencyOrchestrator {
     registry: EcosystemRegistry,
+    config: Config,
+    use_cache: bool,
+}
+
+/// A detected manifest with its workspace members and their parsed dependencies
+struct ProjectManifests<'a> {
+    manifest: DetectedManifest,
+    adapter: &'a dyn EcosystemAdapter,
+    members: Vec<WorkspaceMember>,
+    /// The manifest itself first, then each workspace member's manifest
+    parsed: Vec<(PathBuf, Vec<Dependency>)>,
+}
+
+/// Where the files of an analysis come from and what they can reuse
+struct AnalysisContext<'a> {
+    /// Directory the manifests and sources are read from
+    root: &'a Path,
+    /// Set when analyzing a revision other than the working directory
+    snapshot: Option<&'a RevisionSnapshot>,
+    cache: Option<&'a Arc<AnalysisCache>>,
 }
 
 impl DependencyOrchestrator {
     pub fn new(registry: EcosystemRegistry) -> Self {
-        Self { registry }
+        Self {
+            registry,
+            config: Config::default(),
+            use_cache: true,
+        }
+    }
+
+    /// Use project settings, see [`Config::load`]
+    pub fn with_config(mut self, config: Config) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Whether to reuse and update the analysis cache in the git directory;
+    /// `analyze` also needs [`DependencyQuery::use_cache`]
+    pub fn with_cache(mut self, use_cache: bool) -> Self {
+        self.use_cache = use_cache;
+        self
     }
 
     /// Execute a dependency query
     pub fn analyze(&self, query: DependencyQuery) -> Result<DependencyAnalysis> {
-        // 1. Detect ecosystem from repo
-        let ecosystem = self.registry.detect_from_directory(&query.repo_path)?;
+        // Files come from the working directory, or from the requested revision
+        let snapshot = match &query.revision {
+            Some(revision) => Some(self.snapshot(
+                &query.repo_path,
+                revision,
+                query.manifest.as_deref(),
+                query.scan_usage,
+            )?),
+            None => None,
+        };
+        let root = snapshot
+            .as_ref()
+            .map(|snapshot| snapshot.dir.clone())
+            .unwrap_or_else(|| query.repo_path.clone());
 
-        // 2. Get dependency file path
-        let dep_file = self
-            .registry
-            .get_dependency_file(&query.repo_path, ecosystem)?;
+        // 1. Detect every manifest in the repo and parse it
+        let projects = self.load_projects(&root, query.manifest.as_deref(), query.ecosystem)?;
 
-        // 3. Parse dependency file to find the dependency
-        let adapter = self
-            .registry
-            .get_adapter(ecosystem)
-            .ok_or(DependencyBlameError::UnsupportedEcosystem)?;
+        let cache = if query.use_cache {
+            self.open_cache(&query.repo_path)
+        } else {
+            None
+        };
+        let context = AnalysisContext {
+            root: &root,
+            snapshot: snapshot.as_ref(),
+            cache: cache.as_ref(),
+        };
+
+        // 2. Analyze the dependency in the first manifest that declares it
+        let mut analysis = None;
+        for project in &projects {
+            analysis = self.analyze_in_project(&query, project, &context)?;
+            if analysis.is_some() {
+                break;
+            }
+        }
+
+        // 3. Otherwise it may have been removed, and still have a history
+        if analysis.is_none() && query.include_git_history {
+            analysis = self.analyze_removed(&query, &projects, &context)?;
+        }
+
+        if let Some(analysis) = analysis {
+            // The cache only speeds up later runs, so failing to write it is not an error
+            if let Some(cache) = &cache {
+                let _ = cache.save();
+            }
+            return Ok(analysis);
+        }
 
-        let dependency = adapter
+        Err(DependencyBlameError::DependencyNotFound(
+            query.dependency_name.clone(),
+        ))
+    }
+
+    /// List all dependencies in a project, across every ecosystem and manifest
+    pub fn list_all_dependencies(&self, repo_path: &std::path::Path) -> Result<Vec<Dependency>> {
+        self.list_dependencies(repo_path, None, None, None)
+    }
+
+    /// List dependencies of an explicit manifest and/or ecosystem, see
+    /// [`EcosystemRegistry::select_manifests`], optionally as of a git revision
+    pub fn list_dependencies(
+        &self,
+        repo_path: &Path,
+        manifest: Option<&Path>,
+        ecosystem: Option<EcosystemType>,
+        revision: Option<&str>,
+    ) -> Result<Vec<Dependency>> {
+        let projects = match revision {
+            Some(revision) => {
+                let snapshot = self.snapshot(repo_path, revision, manifest, false)?;
+                self.load_projects(&snapshot.dir, manifest, ecosystem)?
+            }
+            None => self.load_projects(repo_path, manifest, ecosystem)?,
+        };
+
+        Ok(projects
+            .into_iter()
+            .flat_map(|project| project.parsed)
+            .flat_map(|(_, deps)| deps)
+            .collect())
+    }
+
+    /// Compare the dependencies declared at two revisions, with the commits
+    /// in between that changed each of them
+    pub fn diff(
+        &self,
+        repo_path: &Path,
+        from: &str,
+        to: &str,
+        manifest: Option<&Path>,
+        ecosystem: Option<EcosystemType>,
+    ) -> Result<DependencyDiff> {
+        // Only the manifests, lockfiles and workspace definitions of the two
+        // revisions are written out, not their sources
+        let from_snapshot = self.snapshot(repo_path, from, manifest, false)?;
+        let to_snapshot = self.snapshot(repo_path, to, manifest, false)?;
+
+        let mut lockfiles = HashMap::new();
+        let before =
+            self.locked_dependencies(&from_snapshot, manifest, ecosystem, &mut lockfiles)?;
+        let after = self.locked_dependencies(&to_snapshot, manifest, ecosystem, &mut lockfiles)?;
+
+        let mut changes = diff::compare(&before, &after);
+
+        if !changes.is_empty() {
+            let mut files: Vec<PathBuf> =
+                changes.iter().filter_map(|c| c.manifest.clone()).collect();
+            files.extend(lockfiles.values().cloned());
+            files.sort();
+            files.dedup();
+
+            let commits = GitAnalyzer::new(repo_path)?
+                .with_issue_patterns(self.config.issue_patterns())
+                .changes_in_range(from_snapshot.commit, to_snapshot.commit, &files)?;
+            diff::attribute_commits(&mut changes, &commits, &lockfiles);
+        }
+
+        Ok(DependencyDiff {
+            from: from.to_string(),
+            to: to.to_string(),
+            from_commit: from_snapshot.commit.to_string(),
+            to_commit: to_snapshot.commit.to_string(),
+            changes,
+        })
+    }
+
+    /// Dependencies staged for the next commit that HEAD declares in none
+    /// of the manifests, for the commit hooks. Links to workspace members
+    /// are left out.
+    pub fn staged_additions(&self, repo_path: &Path) -> Result<Vec<Dependency>> {
+        let projects = match self.load_projects(repo_path, None, None) {
+            Ok(projects) => projects,
+            Err(DependencyBlameError::EcosystemDetectionFailed(_))
+            | Err(DependencyBlameError::DependencyFileNotFound(_)) => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+        let git_analyzer = GitAnalyzer::new(repo_path)?;
+
+        let mut committed = HashSet::new();
+        let mut staged = Vec::new();
+        for project in &projects {
+            let members: HashSet<&str> = project.members.iter().map(|m| m.name.as_str()).collect();
+            for (manifest, _) in &project.parsed {
+                let (head, index) =
+                    git_analyzer.staged_dependencies(manifest, project.adapter.parser())?;
+                committed.extend(head.into_iter().map(|d| (d.ecosystem, d.name)));
+
+                let relative = manifest.strip_prefix(repo_path).unwrap_or(manifest);
+                for mut dep in index {
+                    if !dep.is_internal && !members.contains(dep.name.as_str()) {
+                        dep.manifest = Some(relative.to_path_buf());
+                        staged.push(dep);
+                    }
+                }
+            }
+        }
+
+        // Moving a dependency to another manifest does not add it
+        let mut seen = HashSet::new();
+        staged.retain(|dep| {
+            let key = (dep.ecosystem, dep.name.clone());
+            !committed.contains(&key) && seen.insert(key)
+        });
+        Ok(staged)
+    }
+
+    /// Parse every manifest of a revision along with its lockfile. Records the
+    /// lockfile of each manifest, relative to the repository root, in `lockfiles`.
+    fn locked_dependencies(
+        &self,
+        snapshot: &RevisionSnapshot,
+        manifest: Option<&Path>,
+        ecosystem: Option<EcosystemType>,
+        lockfiles: &mut HashMap<PathBuf, PathBuf>,
+    ) -> Result<Vec<LockedDependency>> {
+        // A revision from before the project had manifests has no dependencies
+        let projects = match self.load_projects(&snapshot.dir, manifest, ecosystem) {
+            Ok(projects) => projects,
+            Err(DependencyBlameError::EcosystemDetectionFailed(_))
+            | Err(DependencyBlameError::DependencyFileNotFound(_)) => Vec::new(),
+            Err(e) => return Err(e),
+        };
+
+        let mut locked = Vec::new();
+        for project in projects {
+            let lockfile = project.adapter.parser().lockfile(project.manifest.dir());
+            for (_, deps) in project.parsed {
+                for dep in deps {
+                    if let (Some(lockfile), Some(manifest)) = (&lockfile, &dep.manifest) {
+                        let relative = lockfile
+                            .path
+                            .strip_prefix(&snapshot.dir)
+                            .unwrap_or(&lockfile.path);
+                        lockfiles.insert(manifest.clone(), relative.to_path_buf());
+                    }
+                    let version = lockfile
+                        .as_ref()
+                        .and_then(|lockfile| lockfile.versions.get(&dep.name).cloned());
+                    locked.push((dep, version));
+                }
+            }
+        }
+        Ok(locked)
+    }
+
+    /// Analyze the dependency within one project, if its manifest declares or overrides it
+    fn analyze_in_project(
+        &self,
+        query: &DependencyQuery,
+        project: &ProjectManifests,
+        context: &AnalysisContext,
+    ) -> Result<Option<DependencyAnalysis>> {
+        let adapter = project.adapter;
+        let root_manifest = &project.manifest.path;
+
+        // Aliased dependencies can be looked up by their local or real name
+        let found = project.parsed.iter().find_map(|(manifest, deps)| {
+            deps.iter()
+                .find(|d| {
+                    d.name == query.dependency_name
+                        || d.alias_for.as_deref() == Some(query.dependency_name.as_str())
+                })
+                .map(|d| (manifest.clone(), d.clone()))
+        });
+
+        // Version overrides only take effect in the root manifest
+        let package = found
+            .as_ref()
+            .map(|(_, d)| d.alias_for.as_ref().unwrap_or(&d.name).clone())
+            .unwrap_or_else(|| query.dependency_name.clone());
+        let mut overrides: Vec<DependencyOverride> = adapter
             .parser()
-            .find_dependency(&dep_file, &query.dependency_name)?
-            .ok_or_else(|| {
-                DependencyBlameError::DependencyNotFound(query.dependency_name.clone())
-            })?;
+            .parse_overrides(&fs::read_to_string(root_manifest)?)
+            .into_iter()
+            .filter(|o| o.package == package)
+            .collect();
 
-        // 4. Get git information if requested
-        let git_info = if query.include_git_history {
-            match GitAnalyzer::new(&query.repo_path) {
-                Ok(git_analyzer) => git_analyzer
-                    .find_dependency_introduction(&dep_file, &query.dependency_name)
-                    .ok()
-                    .flatten(),
-                Err(_) => None, // Not a git repo or error reading git
+        let (manifest, dependency) = match found {
+            Some(found) => found,
+            // A transitive package whose version is forced is still worth explaining
+            None if !overrides.is_empty() => {
+                let mut dependency = Dependency::new(
+                    package,
+                    overrides[0].version.clone(),
+                    project.manifest.ecosystem,
+                    DependencyType::Override,
+                );
+                dependency.manifest = Some(
+                    root_manifest
+                        .strip_prefix(context.root)
+                        .unwrap_or(root_manifest)
+                        .to_path_buf(),
+                );
+                (root_manifest.clone(), dependency)
             }
+            None => return Ok(None),
+        };
+
+        let declared: Vec<Dependency> = project
+            .parsed
+            .iter()
+            .flat_map(|(_, d)| d.iter().cloned())
+            .collect();
+
+        let typed_package = adapter.parser().typed_package(&dependency, &declared);
+
+        // Get git information if requested
+        let git_analyzer = if query.include_git_history {
+            self.git_analyzer(&query.repo_path, context)
         } else {
             None
         };
 
-        // 5. Scan for usage if requested
-        let usage_info = if query.scan_usage {
-            let usage_scanner = UsageScanner::new(self.create_registry_copy());
-            usage_scanner.scan_usage(&query.repo_path, &dependency)?
-        } else {
-            UsageInfo::new()
+        // Forced versions of transitive packages are not declared, in HEAD or elsewhere
+        let local_change = git_analyzer
+            .as_ref()
+            .filter(|_| dependency.dependency_type != DependencyType::Override)
+            .and_then(|git_analyzer| {
+                git_analyzer
+                    .local_change(&manifest, &dependency.name, adapter.parser())
+                    .ok()
+                    .flatten()
+            });
+        // A dependency that was not committed yet has no history to search
+        let history = git_analyzer.as_ref().filter(|_| local_change.is_none());
+
+        let mut git_info = history.and_then(|git_analyzer| {
+            git_analyzer
+                .find_dependency_introduction(&manifest, &dependency.name, adapter.parser())
+                .ok()
+                .flatten()
+        });
+        if let Some(git_info) = &mut git_info {
+            git_info.explain(&dependency.name);
+        }
+
+        let mut blame = history.and_then(|git_analyzer| {
+            git_analyzer
+                .blame_entry(&manifest, &dependency.name)
+                .ok()
+                .flatten()
+        });
+        let releases = match (history, &git_info) {
+            (Some(git_analyzer), Some(git_info)) => git_analyzer
+                .release_range(
+                    &git_info.commit_hash,
+                    None,
+                    self.config.releases.tags.as_deref(),
+                )
+                .ok()
+                .flatten(),
+            _ => None,
         };
 
-        Ok(DependencyAnalysis {
+        if let Some(blame) = &mut blame {
+            blame.last_changed.explain(&dependency.name);
+            blame.introduced.explain(&dependency.name);
+        }
+
+        if let Some(git_analyzer) = &git_analyzer {
+            for entry in &mut overrides {
+                entry.git_info = git_analyzer
+                    .find_introduction(root_manifest, |content| {
+                        adapter
+                            .parser()
+                            .parse_overrides(content)
+                            .iter()
+                            .any(|o| o.source == entry.source && o.selector == entry.selector)
+                    })
+                    .ok()
+                    .flatten();
+                if let Some(git_info) = &mut entry.git_info {
+                    git_info.explain(&entry.package);
+                }
+            }
+        }
+
+        let shallow = git_analyzer
+            .as_ref()
+            .and_then(|git_analyzer| git_analyzer.shallow_history().ok().flatten());
+        let rationale = self.rationale(
+            context.root,
+            history,
+            dependency.ecosystem,
+            &dependency.name,
+            git_info.as_ref(),
+        )?;
+
+        // Scan for usage if requested, within the manifest's directory. A
+        // package that is only overridden is pulled in by other packages, so
+        // whether the code imports it says nothing about whether it is needed.
+        let usage_info =
+            if query.scan_usage && dependency.dependency_type != DependencyType::Override {
+                self.scan_usage(query, project, context, &dependency)?
+            } else {
+                UsageInfo::new()
+            };
+
+        Ok(Some(DependencyAnalysis {
             dependency,
             git_info,
             usage_info,
+            typed_package,
+            overrides,
+            releases,
+            blame,
+            removed: None,
+            shallow,
+            local_change,
+            rationale,
+        }))
+    }
+
+    /// Analyze a dependency that is no longer declared from the manifest
+    /// history, if it was declared in any of the project manifests before
+    fn analyze_removed(
+        &self,
+        query: &DependencyQuery,
+        projects: &[ProjectManifests],
+        context: &AnalysisContext,
+    ) -> Result<Option<DependencyAnalysis>> {
+        let git_analyzer = match self.git_analyzer(&query.repo_path, context) {
+            Some(git_analyzer) => git_analyzer,
+            None => return Ok(None),
+        };
+
+        for project in projects {
+            for (manifest, _) in &project.parsed {
+                let relative = manifest.strip_prefix(context.root).unwrap_or(manifest);
+                let entry = graveyard::removed_dependencies(
+                    &git_analyzer,
+                    project.adapter.parser(),
+                    manifest,
+                    relative,
+                )
+                .unwrap_or_default()
+                .into_iter()
+                .find(|removed| removed.name == query.dependency_name);
+
+                if let Some(entry) = entry {
+                    let mut dependency = Dependency::new(
+                        entry.name.clone(),
+                        entry.version.clone(),
+                        entry.ecosystem,
+                        entry.dependency_type,
+                    );
+                    dependency.manifest = Some(entry.manifest.clone());
+
+                    // Leftover imports of a removed dependency are worth knowing about
+                    let usage_info = if query.scan_usage {
+                        self.scan_usage(query, project, context, &dependency)?
+                    } else {
+                        UsageInfo::new()
+                    };
+
+                    let releases = git_analyzer
+                        .release_range(
+                            &entry.added.commit_hash,
+                            Some(&entry.removed.commit_hash),
+                            self.config.releases.tags.as_deref(),
+                        )
+                        .ok()
+                        .flatten();
+
+                    let rationale = self.rationale(
+                        context.root,
+                        Some(&git_analyzer),
+                        entry.ecosystem,
+                        &entry.name,
+                        Some(&entry.added),
+                    )?;
+
+                    return Ok(Some(DependencyAnalysis {
+                        dependency,
+                        git_info: Some(entry.added.clone()),
+                        usage_info,
+                        typed_package: None,
+                        overrides: Vec::new(),
+                        releases,
+                        blame: None,
+                        removed: Some(entry),
+                        shallow: git_analyzer.shallow_history().ok().flatten(),
+                        local_change: None,
+                        rationale,
+                    }));
+                }
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Analyze every declared dependency, in the order of the manifests: the
+    /// recorded rationale, the commit that introduced it, the first release
+    /// shipping it and whether it is used
+    pub fn audit(
+        &self,
+        repo_path: &Path,
+        manifest: Option<&Path>,
+        ecosystem: Option<EcosystemType>,
+        revision: Option<&str>,
+        include_git_history: bool,
+        scan_usage: bool,
+    ) -> Result<Vec<DependencyAnalysis>> {
+        let snapshot = match revision {
+            Some(revision) => Some(self.snapshot(repo_path, revision, manifest, scan_usage)?),
+            None => None,
+        };
+        let root = snapshot
+            .as_ref()
+            .map(|snapshot| snapshot.dir.clone())
+            .unwrap_or_else(|| repo_path.to_path_buf());
+        let projects = self.load_projects(&root, manifest, ecosystem)?;
+
+        let cache = self.open_cache(repo_path);
+        let context = AnalysisContext {
+            root: &root,
+            snapshot: snapshot.as_ref(),
+            cache: cache.as_ref(),
+        };
+        let git_analyzer = if include_git_history {
+            self.git_analyzer(repo_path, &context)
+        } else {
+            None
+        };
+
+        let mut analyses = Vec::new();
+        for project in &projects {
+            for (manifest, deps) in &project.parsed {
+                for dependency in deps {
+                    let mut git_info = git_analyzer.as_ref().and_then(|git_analyzer| {
+                        git_analyzer
+                            .find_dependency_introduction(
+                                manifest,
+                                &dependency.name,
+                                project.adapter.parser(),
+                            )
+                            .ok()
+                            .flatten()
+                    });
+                    if let Some(git_info) = &mut git_info {
+                        git_info.explain(&dependency.name);
+                    }
+                    let rationale = self.rationale(
+                        context.root,
+                        git_analyzer.as_ref(),
+                        dependency.ecosystem,
+                        &dependency.name,
+                        git_info.as_ref(),
+                    )?;
+                    let releases = match (&git_analyzer, &git_info) {
+                        (Some(git_analyzer), Some(git_info)) => git_analyzer
+                            .release_range(
+                                &git_info.commit_hash,
+                                None,
+                                self.config.releases.tags.as_deref(),
+                            )
+                            .ok()
+                            .flatten(),
+                        _ => None,
+                    };
+
+                    // Links to other packages of the repository are not worth scanning for
+                    let usage_info = if scan_usage && !dependency.is_internal {
+                        let query =
+                            DependencyQuery::new(dependency.name.clone(), repo_path.to_path_buf());
+                        self.scan_usage(&query, project, &context, dependency)?
+                    } else {
+                        UsageInfo::new()
+                    };
+
+                    analyses.push(DependencyAnalysis {
+                        dependency: dependency.clone(),
+                        git_info,
+                        usage_info,
+                        typed_package: None,
+                        overrides: Vec::new(),
+                        releases,
+                        blame: None,
+                        removed: None,
+                        shallow: None,
+                        local_change: None,
+                        rationale,
+                    });
+                }
+            }
+        }
+
+        if let Some(cache) = &cache {
+            let _ = cache.save();
+        }
+        Ok(analyses)
+    }
+
+    /// List the dependencies that were removed from the project's manifests,
+    /// most recently removed first
+    pub fn graveyard(
+        &self,
+        repo_path: &Path,
+        manifest: Option<&Path>,
+        ecosystem: Option<EcosystemType>,
+        revision: Option<&str>,
+    ) -> Result<Vec<RemovedDependency>> {
+        let snapshot = match revision {
+            Some(revision) => Some(self.snapshot(repo_path, revision, manifest, false)?),
+            None => None,
+        };
+        let root = snapshot
+            .as_ref()
+            .map(|snapshot| snapshot.dir.clone())
+            .unwrap_or_else(|| repo_path.to_path_buf());
+        let projects = self.load_projects(&root, manifest, ecosystem)?;
+
+        let cache = self.open_cache(repo_path);
+        let context = AnalysisContext {
+            root: &root,
+            snapshot: snapshot.as_ref(),
+            cache: cache.as_ref(),
+        };
+        let git_analyzer = self
+            .git_analyzer(repo_path, &context)
+            .ok_or_else(|| DependencyBlameError::GitRepoNotFound(repo_path.to_path_buf()))?;
+
+        // A dependency moved to another manifest is still alive
+        let declared: HashSet<(EcosystemType, &str)> = projects
+            .iter()
+            .flat_map(|project| project.parsed.iter())
+            .flat_map(|(_, deps)| deps.iter().map(|d| (d.ecosystem, d.name.as_str())))
+            .collect();
+
+        let mut removed = Vec::new();
+        for project in &projects {
+            for (manifest, _) in &project.parsed {
+                let relative = manifest.strip_prefix(&root).unwrap_or(manifest);
+                removed.extend(
+                    graveyard::removed_dependencies(
+                        &git_analyzer,
+                        project.adapter.parser(),
+                        manifest,
+                        relative,
+                    )?
+                    .into_iter()
+                    .filter(|entry| !declared.contains(&(entry.ecosystem, entry.name.as_str()))),
+                );
+            }
+        }
+
+        // Manifests deleted from the repository, along with all they declared
+        if manifest.is_none() {
+            let deleted = git_analyzer
+                .deleted_files(|path| self.registry.manifest_ecosystem(path).is_some())?;
+            let canonical_root = root.canonicalize().unwrap_or_else(|_| root.clone());
+            for manifest in deleted {
+                let relative = match manifest
+                    .strip_prefix(&root)
+                    .or_else(|_| manifest.strip_prefix(&canonical_root))
+                {
+                    Ok(relative) => relative,
+                    // Outside of the analyzed directory
+                    Err(_) => continue,
+                };
+                let adapter = match self.registry.manifest_ecosystem(relative) {
+                    Some(found) if ecosystem.is_some_and(|wanted| wanted != found) => continue,
+                    Some(found)
+                        if ecosystem.is_none() && !self.config.is_ecosystem_enabled(found) =>
+                    {
+                        continue
+                    }
+                    Some(found) => match self.registry.get_adapter(found) {
+                        Some(adapter) => adapter,
+                        None => continue,
+                    },
+                    None => continue,
+                };
+                removed.extend(
+                    graveyard::removed_dependencies(
+                        &git_analyzer,
+                        adapter.parser(),
+                        &manifest,
+                        relative,
+                    )?
+                    .into_iter()
+                    .filter(|entry| !declared.contains(&(entry.ecosystem, entry.name.as_str()))),
+                );
+            }
+        }
+
+        if let Some(cache) = &cache {
+            let _ = cache.save();
+        }
+
+        removed.sort_by(|a, b| {
+            b.removed
+                .date
+                .cmp(&a.removed.date)
+                .then(a.name.cmp(&b.name))
+        });
+        Ok(removed)
+    }
+
+    /// Count per author, or per team of the `[teams]` config, the declared
+    /// dependencies they introduced, how many of those look unused and the
+    /// dependencies they removed
+    pub fn authors(
+        &self,
+        repo_path: &Path,
+        manifest: Option<&Path>,
+        ecosystem: Option<EcosystemType>,
+        by_team: bool,
+        scan_usage: bool,
+    ) -> Result<Vec<AuthorStats>> {
+        let projects = self.load_projects(repo_path, manifest, ecosystem)?;
+        let cache = self.open_cache(repo_path);
+        let context = AnalysisContext {
+            root: repo_path,
+            snapshot: None,
+            cache: cache.as_ref(),
+        };
+        let git_analyzer = self
+            .git_analyzer(repo_path, &context)
+            .ok_or_else(|| DependencyBlameError::GitRepoNotFound(repo_path.to_path_buf()))?;
+
+        let mut introductions = Vec::new();
+        for project in &projects {
+            for (manifest, deps) in &project.parsed {
+                for dependency in deps.iter().filter(|d| !d.is_internal) {
+                    let git_info = match git_analyzer.find_dependency_introduction(
+                        manifest,
+                        &dependency.name,
+                        project.adapter.parser(),
+                    )? {
+                        Some(git_info) => git_info,
+                        None => continue,
+                    };
+                    let is_unused = scan_usage && {
+                        let query =
+                            DependencyQuery::new(dependency.name.clone(), repo_path.to_path_buf());
+                        let usage_info = self.scan_usage(&query, project, &context, dependency)?;
+                        !usage_info.is_used && dependency.ignore_reason.is_none()
+                    };
+                    introductions.push((dependency.name.clone(), git_info, is_unused));
+                }
+            }
+        }
+
+        if let Some(cache) = &cache {
+            let _ = cache.save();
+        }
+        let removed = self.graveyard(repo_path, manifest, ecosystem, None)?;
+
+        let introduced: Vec<Introduced> = introductions
+            .iter()
+            .map(|(name, git_info, is_unused)| Introduced {
+                name,
+                git_info,
+                is_unused: *is_unused,
+            })
+            .collect();
+        let teams = self.config.team_patterns();
+        Ok(authors::summarize(&introduced, &removed, |git_info| {
+            if !by_team {
+                return git_info.author.clone();
+            }
+            teams
+                .team_for(authors::author_email(&git_info.author))
+                .unwrap_or("unassigned")
+                .to_string()
+        }))
+    }
+
+    /// Record why a declared dependency exists, in the sidecar file at the
+    /// repository root or, with `in_notes`, in a note on the commit that
+    /// introduced it. With an `ecosystem`, the reason only applies to that
+    /// ecosystem's dependency of the name.
+    pub fn record_rationale(
+        &self,
+        repo_path: &Path,
+        ecosystem: Option<EcosystemType>,
+        dependency_name: &str,
+        reason: &str,
+        in_notes: bool,
+    ) -> Result<RationaleSource> {
+        let projects = self.load_projects(repo_path, None, ecosystem)?;
+        let declaring = projects.iter().find_map(|project| {
+            project
+                .parsed
+                .iter()
+                .find(|(_, deps)| deps.iter().any(|d| d.name == dependency_name))
+                .map(|(manifest, _)| (project, manifest))
+        });
+        let (project, manifest) = declaring
+            .ok_or_else(|| DependencyBlameError::DependencyNotFound(dependency_name.to_string()))?;
+
+        if !in_notes {
+            rationale::write_sidecar(repo_path, ecosystem, dependency_name, reason)?;
+            return Ok(RationaleSource::Sidecar {
+                path: PathBuf::from(rationale::SIDECAR_FILE),
+            });
+        }
+
+        let git_analyzer = GitAnalyzer::new(repo_path)?;
+        let introduced = git_analyzer
+            .find_dependency_introduction(manifest, dependency_name, project.adapter.parser())?
+            .ok_or_else(|| {
+                DependencyBlameError::Other(format!(
+                    "No commit introduced '{}' yet; commit it first or record the reason in {}",
+                    dependency_name,
+                    rationale::SIDECAR_FILE
+                ))
+            })?;
+        git_analyzer.set_note_reason(
+            &introduced.commit_hash,
+            ecosystem,
+            dependency_name,
+            reason,
+        )?;
+        Ok(RationaleSource::Note {
+            commit: introduced.commit_hash,
         })
     }
 
-    /// List all dependencies in a project
-    pub fn list_all_dependencies(&self, repo_path: &std::path::Path) -> Result<Vec<Dependency>> {
-        // 1. Detect ecosystem
-        let ecosystem = self.registry.detect_from_directory(repo_path)?;
+    /// Dependencies the sidecar file gives a reason for that no manifest
+    /// declares any more; `None` when there is no sidecar file
+    pub fn stale_rationales(&self, repo_path: &Path) -> Result<Option<Vec<String>>> {
+        let reasons = match rationale::sidecar_reasons(repo_path)? {
+            Some(reasons) => reasons,
+            None => return Ok(None),
+        };
+        let declared: HashSet<(EcosystemType, String)> = self
+            .list_all_dependencies(repo_path)?
+            .into_iter()
+            .flat_map(|dep| {
+                let ecosystem = dep.ecosystem;
+                std::iter::once(dep.name)
+                    .chain(dep.alias_for)
+                    .map(move |name| (ecosystem, name))
+            })
+            .collect();
+
+        Ok(Some(
+            reasons
+                .names()
+                .into_iter()
+                .filter(|(ecosystem, name)| {
+                    !declared.iter().any(|(declared_in, declared_name)| {
+                        declared_name == name && ecosystem.is_none_or(|e| e == *declared_in)
+                    })
+                })
+                .map(|(ecosystem, name)| match ecosystem {
+                    Some(ecosystem) => format!("{} ({})", name, ecosystem.as_str()),
+                    None => name.to_string(),
+                })
+                .collect(),
+        ))
+    }
+
+    /// The reason recorded for a dependency: from the sidecar file under
+    /// `root`, or else from a note on the commit that introduced it
+    fn rationale(
+        &self,
+        root: &Path,
+        git_analyzer: Option<&GitAnalyzer>,
+        ecosystem: EcosystemType,
+        dependency_name: &str,
+        introduced: Option<&GitInfo>,
+    ) -> Result<Option<Rationale>> {
+        if let Some(reason) = rationale::sidecar_reasons(root)?
+            .and_then(|reasons| reasons.get(ecosystem, dependency_name).map(String::from))
+        {
+            return Ok(Some(Rationale {
+                reason,
+                source: RationaleSource::Sidecar {
+                    path: PathBuf::from(rationale::SIDECAR_FILE),
+                },
+            }));
+        }
+
+        let note = match (git_analyzer, introduced) {
+            (Some(git_analyzer), Some(introduced)) => git_analyzer
+                .note_reason(&introduced.commit_hash, ecosystem, dependency_name)?
+                .map(|reason| Rationale {
+                    reason,
+                    source: RationaleSource::Note {
+                        commit: introduced.commit_hash.clone(),
+                    },
+                }),
+            _ => None,
+        };
+        Ok(note)
+    }
+
+    /// The analysis cache of the repository, unless it is disabled
+    fn open_cache(&self, repo_path: &Path) -> Option<Arc<AnalysisCache>> {
+        if !self.use_cache {
+            return None;
+        }
+        AnalysisCache::open(repo_path).map(Arc::new)
+    }
+
+    /// The files of `revision` that parsing its manifests needs, or the
+    /// explicitly given `manifest`, and with `scan` those that scanning for
+    /// usages needs too. The sidecar file is always included.
+    fn snapshot(
+        &self,
+        repo_path: &Path,
+        revision: &str,
+        manifest: Option<&Path>,
+        scan: bool,
+    ) -> Result<RevisionSnapshot> {
+        RevisionSnapshot::load(repo_path, revision, |path| {
+            manifest == Some(path)
+                || path == Path::new(rationale::SIDECAR_FILE)
+                || self.registry.parses_file(path)
+                || (scan && self.registry.reads_file(path))
+        })
+    }
+
+    /// Open the repository for history analysis, using the cache and the
+    /// analyzed revision of `context`
+    fn git_analyzer(&self, repo_path: &Path, context: &AnalysisContext) -> Option<GitAnalyzer> {
+        let mut git_analyzer = GitAnalyzer::new(repo_path)
+            .ok()? // Not a git repo or error reading git
+            .with_issue_patterns(self.config.issue_patterns());
+        if let Some(cache) = context.cache {
+            git_analyzer = git_analyzer.with_cache(Arc::clone(cache));
+        }
+        if let Some(snapshot) = context.snapshot {
+            git_analyzer = git_analyzer.with_revision(snapshot.commit, snapshot.dir.clone());
+        }
+        Some(git_analyzer)
+    }
+
+    /// Scan the project's directory for usages of the dependency
+    fn scan_usage(
+        &self,
+        query: &DependencyQuery,
+        project: &ProjectManifests,
+        context: &AnalysisContext,
+        dependency: &Dependency,
+    ) -> Result<UsageInfo> {
+        let mut usage_scanner = UsageScanner::new(self.create_registry_copy())
+            .with_config(self.config.clone(), context.root.to_path_buf());
+        if let Some(cache) = context.cache {
+            usage_scanner = usage_scanner.with_cache(Arc::clone(cache));
+        }
+        let mut usage_info = usage_scanner.scan_usage(project.manifest.dir(), dependency)?;
+        attribute_workspaces(&mut usage_info.import_locations, &project.members);
 
-        // 2. Get dependency file
-        let dep_file = self.registry.get_dependency_file(repo_path, ecosystem)?;
+        // Report files of a revision at their place in the repository
+        if let Some(snapshot) = context.snapshot {
+            for location in &mut usage_info.import_locations {
+                location.file_path = snapshot.to_repo_path(&location.file_path, &query.repo_path);
+            }
+        }
+        Ok(usage_info)
+    }
+
+    /// Detect and parse every manifest in the repository.
+    ///
+    /// Manifests that belong to another manifest's workspace are parsed as
+    /// part of that workspace rather than on their own. Parse errors in the
+    /// repository root or in an explicitly selected manifest are reported;
+    /// unreadable manifests elsewhere are skipped.
+    fn load_projects(
+        &self,
+        repo_path: &Path,
+        manifest: Option<&Path>,
+        ecosystem: Option<EcosystemType>,
+    ) -> Result<Vec<ProjectManifests<'_>>> {
+        let mut projects = Vec::new();
+        let mut workspace_manifests = HashSet::new();
+        let explicit = manifest.is_some();
 
-        // 3. Parse all dependencies
-        let adapter = self
+        for manifest in self
             .registry
-            .get_adapter(ecosystem)
-            .ok_or(DependencyBlameError::UnsupportedEcosystem)?;
+            .select_manifests(repo_path, manifest, ecosystem)?
+        {
+            if workspace_manifests.contains(&manifest.path) {
+                continue;
+            }
+            // Explicitly selected ecosystems are analyzed even when disabled in the config
+            if !explicit
+                && ecosystem.is_none()
+                && !self.config.is_ecosystem_enabled(manifest.ecosystem)
+            {
+                continue;
+            }
 
-        adapter.parser().parse_dependencies(&dep_file)
+            let adapter = self
+                .registry
+                .get_adapter(manifest.ecosystem)
+                .ok_or(DependencyBlameError::UnsupportedEcosystem)?;
+
+            let members = adapter.parser().workspace_members(manifest.dir());
+            let parsed = match parse_manifests(adapter, &manifest.path, &members) {
+                Ok(mut parsed) => {
+                    for (path, deps) in &mut parsed {
+                        let relative = path.strip_prefix(repo_path).unwrap_or(path);
+                        for dep in deps {
+                            dep.manifest = Some(relative.to_path_buf());
+                            dep.ignore_reason =
+                                self.config.ignore_reason(&dep.name).map(String::from);
+                        }
+                    }
+                    parsed
+                }
+                Err(e) if explicit || manifest.dir() == repo_path => return Err(e),
+                Err(_) => continue,
+            };
+
+            workspace_manifests.extend(members.iter().map(|m| m.manifest.clone()));
+            projects.push(ProjectManifests {
+                manifest,
+                adapter,
+                members,
+                parsed,
+            });
+        }
+
+        Ok(projects)
     }
 
     // Helper to create a copy of the registry for UsageScanner
@@ -87,3 +1058,46 @@ impl DependencyOrchestrator {
         crate::ecosystems::registry::create_default_registry()
     }
 }
+
+/// Parse the root manifest and every workspace member's manifest.
+///
+/// Member dependencies are tagged with the member name, and dependencies on
+/// other members are marked internal. Unreadable member manifests are skipped.
+fn parse_manifests(
+    adapter: &dyn EcosystemAdapter,
+    root_manifest: &Path,
+    members: &[WorkspaceMember],
+) -> Result<Vec<(PathBuf, Vec<Dependency>)>> {
+    let mut manifests = vec![(
+        root_manifest.to_path_buf(),
+        adapter.parser().parse_dependencies(root_manifest)?,
+    )];
+
+    for member in members {
+        if let Ok(mut deps) = adapter.parser().parse_dependencies(&member.manifest) {
+            for dep in &mut deps {
+                dep.workspace = Some(member.name.clone());
+            }
+            manifests.push((member.manifest.clone(), deps));
+        }
+    }
+
+    for (_, deps) in &mut manifests {
+        for dep in deps {
+            dep.is_internal |= members.iter().any(|m| m.name == dep.name);
+        }
+    }
+
+    Ok(manifests)
+}
+
+/// Attribute each location to the innermost workspace member containing it
+fn attribute_workspaces(locations: &mut [ImportLocation], members: &[WorkspaceMember]) {
+    for location in locations {
+        location.workspace = members
+            .iter()
+            .filter(|m| location.file_path.starts_with(&m.dir))
+            .max_by_key(|m| m.dir.components().count())
+            .map(|m| m.name.clone());
+    }
+}
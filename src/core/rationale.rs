@@ -0,0 +1,378 @@
+use crate::core::config::ecosystem_key;
+use crate::core::dependency::EcosystemType;
+use crate::core::error::{DependencyBlameError, Result};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// Checked-in file of dependency rationale, at the repository root
+pub const SIDECAR_FILE: &str = "DEPENDENCIES.toml";
+
+/// Notes ref holding rationale attached to the commits that introduced dependencies
+pub const NOTES_REF: &str = "refs/notes/dependency-blame";
+
+/// Why a dependency exists, as recorded with `explain set`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Rationale {
+    pub reason: String,
+    pub source: RationaleSource,
+}
+
+/// Where a rationale was recorded
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum RationaleSource {
+    /// The sidecar file, relative to the repository
+    Sidecar { path: PathBuf },
+    /// A note on the commit that introduced the dependency
+    Note { commit: String },
+}
+
+/// Reasons keyed by dependency name, the format of both the sidecar file
+/// and the notes: `serde = "Serialization of the config"`.
+///
+/// A name can be declared by several ecosystems, such as the npm and the
+/// PyPI `debug`. A plain reason applies to all of them; one in a table
+/// named after an ecosystem (`[node]`, `[python]`, ...) only to that
+/// ecosystem's dependency, and wins over a plain one.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Reasons {
+    #[serde(flatten)]
+    entries: BTreeMap<String, ReasonEntry>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+enum ReasonEntry {
+    Reason(String),
+    Ecosystem(BTreeMap<String, String>),
+}
+
+impl Reasons {
+    /// The reason for the dependency of `ecosystem` named `dependency_name`
+    pub fn get(&self, ecosystem: EcosystemType, dependency_name: &str) -> Option<&str> {
+        let scoped = match self.entries.get(ecosystem_key(ecosystem)) {
+            Some(ReasonEntry::Ecosystem(reasons)) => reasons.get(dependency_name),
+            _ => None,
+        };
+        let plain = match self.entries.get(dependency_name) {
+            Some(ReasonEntry::Reason(reason)) => Some(reason),
+            _ => None,
+        };
+        scoped.or(plain).map(String::as_str)
+    }
+
+    /// Every dependency given a reason, with the ecosystem it is limited to
+    pub fn names(&self) -> Vec<(Option<EcosystemType>, &str)> {
+        let mut names = Vec::new();
+        for (key, entry) in &self.entries {
+            match entry {
+                ReasonEntry::Reason(_) => names.push((None, key.as_str())),
+                ReasonEntry::Ecosystem(reasons) => {
+                    let ecosystem = ecosystem_named(key);
+                    names.extend(reasons.keys().map(|name| (ecosystem, name.as_str())));
+                }
+            }
+        }
+        names
+    }
+}
+
+/// Parse reasons in the format of the sidecar file and the notes
+pub fn parse_reasons(content: &str) -> std::result::Result<Reasons, toml::de::Error> {
+    let reasons: Reasons = toml::from_str(content)?;
+    for (key, entry) in &reasons.entries {
+        if matches!(entry, ReasonEntry::Ecosystem(_)) && ecosystem_named(key).is_none() {
+            return Err(serde::de::Error::custom(format!(
+                "[{}] is not an ecosystem; use rust, node, python, go or deno",
+                key
+            )));
+        }
+    }
+    Ok(reasons)
+}
+
+/// The reasons in the sidecar file under `root`, if there is one
+pub fn sidecar_reasons(root: &Path) -> Result<Option<Reasons>> {
+    let path = root.join(SIDECAR_FILE);
+    if !path.is_file() {
+        return Ok(None);
+    }
+    let content = fs::read_to_string(&path)?;
+    parse_reasons(&content)
+        .map(Some)
+        .map_err(|e| DependencyBlameError::ParseError {
+            file: path.display().to_string(),
+            reason: e.to_string(),
+        })
+}
+
+/// Record a reason in the sidecar file under `root`, creating it if needed.
+/// With an `ecosystem`, it only applies to that ecosystem's dependency.
+pub fn write_sidecar(
+    root: &Path,
+    ecosystem: Option<EcosystemType>,
+    dependency_name: &str,
+    reason: &str,
+) -> Result<PathBuf> {
+    let path = root.join(SIDECAR_FILE);
+    let content = match fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(_) => "# Why each dependency exists, see `dependency-blame explain`\n".to_string(),
+    };
+    let updated = set_reason(&content, ecosystem, dependency_name, reason);
+    if let Err(e) = parse_reasons(&updated) {
+        return Err(DependencyBlameError::ParseError {
+            file: path.display().to_string(),
+            reason: e.to_string(),
+        });
+    }
+    fs::write(&path, updated)?;
+    Ok(path)
+}
+
+/// Set the reason of a dependency in reasons `content`, at the top level
+/// or with an `ecosystem` in its table. An existing entry is replaced,
+/// even one spanning several lines, and a new one goes at the end of the
+/// top level or table, which is added when missing. Other lines, comments
+/// included, are kept as they are. The reason is put on one line.
+pub fn set_reason(
+    content: &str,
+    ecosystem: Option<EcosystemType>,
+    dependency_name: &str,
+    reason: &str,
+) -> String {
+    let reason = reason.split_whitespace().collect::<Vec<_>>().join(" ");
+    let entry = format!(
+        "{} = {}",
+        toml_key(dependency_name),
+        toml::Value::String(reason)
+    );
+    let table = ecosystem.map(ecosystem_key);
+
+    let mut lines: Vec<String> = content.lines().map(str::to_string).collect();
+    let layout = Layout::read(&lines);
+    match layout.sections.iter().find(|s| s.table.as_deref() == table) {
+        Some(section) => {
+            let existing = layout
+                .entries
+                .iter()
+                .find(|e| e.key == dependency_name && section.lines.contains(&e.lines.start));
+            match existing {
+                Some(existing) => {
+                    lines.splice(existing.lines.clone(), [entry]);
+                }
+                None => {
+                    // After the section's last line that is not blank
+                    let at = section
+                        .lines
+                        .clone()
+                        .rev()
+                        .find(|&i| !lines[i].trim().is_empty())
+                        .map(|i| i + 1)
+                        .unwrap_or(section.lines.start);
+                    lines.insert(at, entry);
+                }
+            }
+        }
+        None => {
+            if lines.last().is_some_and(|line| !line.trim().is_empty()) {
+                lines.push(String::new());
+            }
+            lines.push(format!("[{}]", table.unwrap_or_default()));
+            lines.push(entry);
+        }
+    }
+
+    let mut updated = lines.join("\n");
+    updated.push('\n');
+    updated
+}
+
+/// Where the entries and tables of reasons content are, by line
+struct Layout {
+    /// The top level, then each table from its header on
+    sections: Vec<Section>,
+    entries: Vec<Entry>,
+}
+
+struct Section {
+    table: Option<String>,
+    lines: Range<usize>,
+}
+
+struct Entry {
+    key: String,
+    /// More than one for a multi-line string
+    lines: Range<usize>,
+}
+
+impl Layout {
+    fn read(lines: &[String]) -> Self {
+        static KEY: OnceLock<Regex> = OnceLock::new();
+        static HEADER: OnceLock<Regex> = OnceLock::new();
+        let key_pattern = KEY.get_or_init(|| {
+            Regex::new(r#"^\s*("(?:[^"\\]|\\.)*"|'[^']*'|[A-Za-z0-9_-]+)\s*=\s*"#)
+                .expect("valid regex")
+        });
+        let header_pattern = HEADER.get_or_init(|| {
+            Regex::new(r#"^\s*\[\s*("[^"]*"|'[^']*'|[A-Za-z0-9_-]+)\s*\]\s*(?:#.*)?$"#)
+                .expect("valid regex")
+        });
+        let unquote = |key: &str| key.trim_matches(|c| c == '"' || c == '\'').to_string();
+
+        let mut sections = vec![Section {
+            table: None,
+            lines: 0..lines.len(),
+        }];
+        let mut entries = Vec::new();
+        let mut i = 0;
+        while i < lines.len() {
+            if let Some(captures) = header_pattern.captures(&lines[i]) {
+                if let Some(current) = sections.last_mut() {
+                    current.lines.end = i;
+                }
+                sections.push(Section {
+                    table: Some(unquote(&captures[1])),
+                    lines: i..lines.len(),
+                });
+            } else if let Some(captures) = key_pattern.captures(&lines[i]) {
+                let value = &lines[i][captures[0].len()..];
+                let mut end = i;
+                for delimiter in [r#"""""#, "'''"] {
+                    let unclosed = value
+                        .strip_prefix(delimiter)
+                        .is_some_and(|rest| !rest.contains(delimiter));
+                    if unclosed {
+                        end = (i + 1..lines.len())
+                            .find(|&j| lines[j].contains(delimiter))
+                            .unwrap_or(lines.len() - 1);
+                    }
+                }
+                entries.push(Entry {
+                    key: unquote(&captures[1]),
+                    lines: i..end + 1,
+                });
+                i = end;
+            }
+            i += 1;
+        }
+
+        Self { sections, entries }
+    }
+}
+
+/// The ecosystem whose table in reasons content is named `key`
+fn ecosystem_named(key: &str) -> Option<EcosystemType> {
+    [
+        EcosystemType::Rust,
+        EcosystemType::Node,
+        EcosystemType::Python,
+        EcosystemType::Go,
+        EcosystemType::Deno,
+    ]
+    .into_iter()
+    .find(|&ecosystem| ecosystem_key(ecosystem) == key)
+}
+
+/// A TOML key for a dependency name, quoted unless it is a bare key
+fn toml_key(name: &str) -> String {
+    let is_bare = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    if is_bare {
+        name.to_string()
+    } else {
+        toml::Value::String(name.to_string()).to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entries_are_replaced_in_place() {
+        let content =
+            "# Why each dependency exists\nserde = \"Old reason\"\n\"@scope/pkg\" = \"Scoped\"\n";
+
+        let updated = set_reason(content, None, "serde", "Reads   the\nconfig");
+        assert_eq!(
+            updated,
+            "# Why each dependency exists\nserde = \"Reads the config\"\n\"@scope/pkg\" = \"Scoped\"\n"
+        );
+        let updated = set_reason(&updated, None, "@scope/pkg", "Still scoped");
+        assert!(updated.contains("\"@scope/pkg\" = \"Still scoped\"\n"));
+        assert_eq!(updated.lines().count(), 3);
+    }
+
+    #[test]
+    fn multi_line_reasons_are_replaced_whole() {
+        let content = "serde = \"\"\"\nReads the config.\nlog = \"is not a key here\"\n\"\"\"\nanyhow = '''\nErrors'''\n";
+
+        let updated = set_reason(content, None, "serde", "Reads the config");
+        assert_eq!(
+            updated,
+            "serde = \"Reads the config\"\nanyhow = '''\nErrors'''\n"
+        );
+
+        let updated = set_reason(content, None, "log", "Logging");
+        let reasons = parse_reasons(&updated).unwrap();
+        assert_eq!(reasons.get(EcosystemType::Rust, "log"), Some("Logging"));
+        assert!(reasons
+            .get(EcosystemType::Rust, "serde")
+            .unwrap()
+            .contains("is not a key here"));
+    }
+
+    #[test]
+    fn reasons_scoped_to_an_ecosystem() {
+        let content = "serde = \"Serialization\"\n\n[node]\ndebug = \"Logging in the web app\"\n";
+
+        // A plain reason goes to the top level, not into the table
+        let updated = set_reason(content, None, "debug", "Debug output");
+        assert_eq!(
+            updated,
+            "serde = \"Serialization\"\ndebug = \"Debug output\"\n\n[node]\ndebug = \"Logging in the web app\"\n"
+        );
+        let reasons = parse_reasons(&updated).unwrap();
+        assert_eq!(
+            reasons.get(EcosystemType::Node, "debug"),
+            Some("Logging in the web app")
+        );
+        assert_eq!(
+            reasons.get(EcosystemType::Python, "debug"),
+            Some("Debug output")
+        );
+
+        let updated = set_reason(&updated, Some(EcosystemType::Python), "debug", "Tracing");
+        let updated = set_reason(&updated, Some(EcosystemType::Node), "debug", "Logging");
+        assert!(updated.ends_with("[node]\ndebug = \"Logging\"\n\n[python]\ndebug = \"Tracing\"\n"));
+        let reasons = parse_reasons(&updated).unwrap();
+        assert_eq!(reasons.get(EcosystemType::Python, "debug"), Some("Tracing"));
+        assert_eq!(
+            reasons.get(EcosystemType::Go, "debug"),
+            Some("Debug output")
+        );
+        assert_eq!(
+            reasons.names(),
+            [
+                (None, "debug"),
+                (Some(EcosystemType::Node), "debug"),
+                (Some(EcosystemType::Python), "debug"),
+                (None, "serde"),
+            ]
+        );
+    }
+
+    #[test]
+    fn tables_must_be_ecosystems() {
+        assert!(parse_reasons("[nodejs]\ndebug = \"Logging\"\n").is_err());
+        assert!(parse_reasons("node = \"The node package\"\n").is_ok());
+    }
+}
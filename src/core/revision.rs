@@ -0,0 +1,140 @@
+use crate::core::error::{DependencyBlameError, Result};
+use git2::{ObjectType, Oid, Repository, Tree};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+/// The files of a commit, read from its tree without touching the working directory.
+///
+/// Manifest parsers and scanners work on files, so the files they read are
+/// written out to a temporary directory, which is removed when the snapshot
+/// is dropped. This works in bare repositories too.
+///
+/// The directory is not a git repository, so walks over it must not require
+/// one for `.gitignore` files to apply.
+pub struct RevisionSnapshot {
+    /// The commit the revision resolved to
+    pub commit: Oid,
+    /// Directory holding the commit's files
+    pub dir: PathBuf,
+    /// Deletes `dir` when the snapshot is dropped
+    _files: TempDir,
+}
+
+impl RevisionSnapshot {
+    /// Resolve `revision` (a commit, branch, tag or any other commit-ish) in
+    /// the repository containing `repo_path` and make the files `is_wanted`
+    /// selects by their path in the repository available, together with
+    /// every `.gitignore`
+    pub fn load<F>(repo_path: &Path, revision: &str, is_wanted: F) -> Result<Self>
+    where
+        F: Fn(&Path) -> bool,
+    {
+        let repo = Repository::discover(repo_path)
+            .map_err(|_| DependencyBlameError::GitRepoNotFound(repo_path.to_path_buf()))?;
+        let commit = repo
+            .revparse_single(revision)
+            .and_then(|object| object.peel_to_commit())
+            .map_err(|_| DependencyBlameError::RevisionNotFound(revision.to_string()))?;
+        let tree = commit.tree()?;
+
+        let files = tempfile::Builder::new()
+            .prefix("dependency-blame-")
+            .tempdir()?;
+        let dir = files.path().to_path_buf();
+        let is_wanted = |path: &Path| path.ends_with(".gitignore") || is_wanted(path);
+        write_tree(&repo, &tree, &dir, Path::new(""), &is_wanted)?;
+
+        Ok(Self {
+            commit: commit.id(),
+            dir,
+            _files: files,
+        })
+    }
+
+    /// Map a path inside the snapshot back to the same path under `repo_path`
+    pub fn to_repo_path(&self, path: &Path, repo_path: &Path) -> PathBuf {
+        match path.strip_prefix(&self.dir) {
+            Ok(relative) => repo_path.join(relative),
+            Err(_) => path.to_path_buf(),
+        }
+    }
+}
+
+/// Write the blobs of `tree` that `is_wanted` selects by their path
+/// under `prefix` to the same path under `dir`. Submodules and symlinks are
+/// skipped.
+fn write_tree(
+    repo: &Repository,
+    tree: &Tree,
+    dir: &Path,
+    prefix: &Path,
+    is_wanted: &dyn Fn(&Path) -> bool,
+) -> Result<()> {
+    for entry in tree.iter() {
+        let name = match entry.name() {
+            Some(name) => name,
+            None => continue,
+        };
+        let relative = prefix.join(name);
+
+        match entry.kind() {
+            Some(ObjectType::Tree) => {
+                write_tree(
+                    repo,
+                    &repo.find_tree(entry.id())?,
+                    dir,
+                    &relative,
+                    is_wanted,
+                )?;
+            }
+            // 0o120000 is a symlink, whose blob holds the link target
+            Some(ObjectType::Blob) if entry.filemode() != 0o120000 && is_wanted(&relative) => {
+                let path = dir.join(&relative);
+                fs::create_dir_all(path.parent().unwrap_or(dir))?;
+                fs::write(&path, repo.find_blob(entry.id())?.content())?;
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use git2::Signature;
+
+    #[test]
+    fn only_wanted_files_and_gitignores_are_written() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        let files = [
+            ("Cargo.toml", "[dependencies]\n"),
+            ("src/main.rs", "fn main() {}\n"),
+            ("docs/logo.svg", "<svg/>\n"),
+            ("docs/.gitignore", "build/\n"),
+        ];
+        let mut index = repo.index().unwrap();
+        for (path, content) in files {
+            let path = Path::new(path);
+            fs::create_dir_all(dir.path().join(path.parent().unwrap())).unwrap();
+            fs::write(dir.path().join(path), content).unwrap();
+            index.add_path(path).unwrap();
+        }
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let signature = Signature::now("A", "a@example.com").unwrap();
+        repo.commit(Some("HEAD"), &signature, &signature, "Add", &tree, &[])
+            .unwrap();
+
+        let snapshot = RevisionSnapshot::load(dir.path(), "HEAD", |path| {
+            path.extension()
+                .is_some_and(|ext| ext == "toml" || ext == "rs")
+        })
+        .unwrap();
+        assert!(snapshot.dir.join("Cargo.toml").is_file());
+        assert!(snapshot.dir.join("src/main.rs").is_file());
+        assert!(snapshot.dir.join("docs/.gitignore").is_file());
+        assert!(!snapshot.dir.join("docs/logo.svg").exists());
+    }
+}
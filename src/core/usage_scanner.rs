@@ -1,19 +1,46 @@
-use crate::core::dependency::{Dependency, ImportLocation, UsageInfo};
+use crate::core::cache::{self, AnalysisCache, CachedImport, CachedSegment};
+use crate::core::config::Config;
+use crate::core::dependency::{Dependency, EcosystemType, ImportLocation, UsageInfo};
 use crate::core::error::Result;
 use crate::ecosystems::registry::EcosystemRegistry;
 use crate::ecosystems::traits::ImportScanner;
+use crate::utils::patterns::PathGlobs;
 use ignore::WalkBuilder;
 use rayon::prelude::*;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 pub struct UsageScanner {
     registry: EcosystemRegistry,
+    config: Config,
+    /// Directory that config paths and globs are relative to
+    repo_root: Option<PathBuf>,
+    cache: Option<Arc<AnalysisCache>>,
 }
 
 impl UsageScanner {
     pub fn new(registry: EcosystemRegistry) -> Self {
-        Self { registry }
+        Self {
+            registry,
+            config: Config::default(),
+            repo_root: None,
+            cache: None,
+        }
+    }
+
+    /// Apply the scan paths, excludes, test paths and import names of a
+    /// project config whose paths are relative to `repo_root`
+    pub fn with_config(mut self, config: Config, repo_root: PathBuf) -> Self {
+        self.config = config;
+        self.repo_root = Some(repo_root);
+        self
+    }
+
+    /// Reuse import extraction results of unchanged files from `cache`
+    pub fn with_cache(mut self, cache: Arc<AnalysisCache>) -> Self {
+        self.cache = Some(cache);
+        self
     }
 
     /// Scan entire codebase for dependency usage
@@ -30,10 +57,26 @@ impl UsageScanner {
             .map(|s| s.to_string())
             .collect();
 
+        let repo_root = self.repo_root.as_deref().unwrap_or(repo_path);
+        let scan_config = self.config.scan_for(dependency.ecosystem);
+        let excludes = PathGlobs::new(&scan_config.exclude);
+        let test_paths = PathGlobs::new(&scan_config.all_test_paths());
+        let import_names = self.config.import_names(dependency);
+        let relative = |path: &Path| path.strip_prefix(repo_root).unwrap_or(path).to_path_buf();
+
         // Build file list using ignore crate (respects .gitignore)
-        let files: Vec<_> = WalkBuilder::new(repo_path)
+        let mut walker = WalkBuilder::new(repo_path);
+        for extra in &scan_config.paths {
+            let extra = repo_root.join(extra);
+            if extra.is_dir() && !extra.starts_with(repo_path) {
+                walker.add(extra);
+            }
+        }
+        let files: Vec<_> = walker
             .hidden(false) // Include hidden files
             .git_ignore(true) // Respect .gitignore
+            .require_git(false) // Also in the snapshot of a revision
+            .filter_entry(cache::skip_cache_dir(repo_path))
             .build()
             .filter_map(|entry| entry.ok())
             .filter(|entry| entry.file_type().map(|ft| ft.is_file()).unwrap_or(false))
@@ -45,16 +88,35 @@ impl UsageScanner {
                     .map(|ext| extensions.contains(&ext.to_string()))
                     .unwrap_or(false)
             })
+            .filter(|entry| !excludes.is_match(&relative(entry.path())))
             .map(|entry| entry.path().to_path_buf())
             .collect();
 
         // Parallel scan with rayon
-        let import_locations: Vec<ImportLocation> = files
+        let mut import_locations: Vec<ImportLocation> = files
             .par_iter()
-            .filter_map(|file_path| self.scan_file(file_path, dependency, scanner).ok())
+            .filter_map(|file_path| {
+                self.scan_file(file_path, dependency, scanner, &import_names)
+                    .ok()
+            })
             .flatten()
             .collect();
 
+        // Skip implicit usages on lines already reported as imports (e.g. a
+        // `require()` inside a JS config file)
+        for implicit in scanner.implicit_usages(repo_path, dependency) {
+            let already_found = import_locations.iter().any(|l| {
+                l.file_path == implicit.file_path && l.line_number == implicit.line_number
+            });
+            if !already_found && !excludes.is_match(&relative(&implicit.file_path)) {
+                import_locations.push(implicit);
+            }
+        }
+
+        for location in &mut import_locations {
+            location.is_test = test_paths.is_match(&relative(&location.file_path));
+        }
+
         Ok(UsageInfo::with_locations(import_locations))
     }
 
@@ -64,21 +126,95 @@ impl UsageScanner {
         file_path: &Path,
         dependency: &Dependency,
         scanner: &dyn ImportScanner,
+        import_names: &[String],
     ) -> Result<Vec<ImportLocation>> {
-        let content = fs::read_to_string(file_path)?;
         let mut locations = Vec::new();
 
-        // Check each line for imports
-        for (line_num, line) in content.lines().enumerate() {
-            if scanner.is_dependency_imported(line, &dependency.name) {
+        for segment in self.file_imports(file_path, dependency.ecosystem, scanner)? {
+            let mut last_line = None;
+
+            for import in segment.imports {
+                let matches = scanner.dependency_matches(&import.module, dependency)
+                    || import_names
+                        .iter()
+                        .any(|name| scanner.import_matches(&import.module, name));
+                if !matches {
+                    continue;
+                }
+
+                // Report each line once, even if it imports the dependency twice
+                if last_line == Some((import.line, import.kind)) {
+                    continue;
+                }
+                last_line = Some((import.line, import.kind));
+
                 locations.push(ImportLocation {
                     file_path: file_path.to_path_buf(),
-                    line_number: line_num + 1, // 1-indexed
-                    line_content: line.trim().to_string(),
+                    line_number: segment.line_offset + import.line + 1, // 1-indexed
+                    line_content: import.text,
+                    cell_index: segment.cell_index,
+                    kind: import.kind,
+                    workspace: None,
+                    is_test: false,
                 });
             }
         }
 
         Ok(locations)
     }
+
+    /// Every import in every scannable segment of a file, from the cache when
+    /// the file content is unchanged
+    fn file_imports(
+        &self,
+        file_path: &Path,
+        ecosystem: EcosystemType,
+        scanner: &dyn ImportScanner,
+    ) -> Result<Vec<CachedSegment>> {
+        let bytes = fs::read(file_path)?;
+        if let Some(segments) = self
+            .cache
+            .as_ref()
+            .and_then(|cache| cache.imports(ecosystem, scanner.version(), file_path, &bytes))
+        {
+            return Ok(segments);
+        }
+
+        let content = String::from_utf8(bytes)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+        let mut segments = Vec::new();
+
+        for segment in scanner.source_segments(file_path, &content) {
+            let lines: Vec<&str> = segment.content.lines().collect();
+            let imports = scanner
+                .find_imports(&segment.content)?
+                .into_iter()
+                .map(|import| CachedImport {
+                    text: lines
+                        .get(import.line)
+                        .map(|l| l.trim().to_string())
+                        .unwrap_or_default(),
+                    module: import.module,
+                    line: import.line,
+                    kind: import.kind,
+                })
+                .collect();
+            segments.push(CachedSegment {
+                line_offset: segment.line_offset,
+                cell_index: segment.cell_index,
+                imports,
+            });
+        }
+
+        if let Some(cache) = &self.cache {
+            cache.set_imports(
+                ecosystem,
+                scanner.version(),
+                file_path,
+                content.as_bytes(),
+                segments.clone(),
+            );
+        }
+        Ok(segments)
+    }
 }
@@ -0,0 +1,40 @@
+mod parser;
+mod scanner;
+mod specifier;
+
+use crate::core::dependency::EcosystemType;
+use crate::ecosystems::traits::{DependencyParser, EcosystemAdapter, ImportScanner};
+
+pub struct DenoAdapter {
+    parser: parser::DenoParser,
+    scanner: scanner::DenoScanner,
+}
+
+impl Default for DenoAdapter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DenoAdapter {
+    pub fn new() -> Self {
+        Self {
+            parser: parser::DenoParser,
+            scanner: scanner::DenoScanner,
+        }
+    }
+}
+
+impl EcosystemAdapter for DenoAdapter {
+    fn parser(&self) -> &dyn DependencyParser {
+        &self.parser
+    }
+
+    fn scanner(&self) -> &dyn ImportScanner {
+        &self.scanner
+    }
+
+    fn ecosystem_type(&self) -> EcosystemType {
+        EcosystemType::Deno
+    }
+}
@@ -0,0 +1,92 @@
+use crate::core::dependency::{Dependency, DependencyType, EcosystemType};
+use crate::core::error::{DependencyBlameError, Result};
+use crate::ecosystems::deno::specifier;
+use crate::ecosystems::traits::DependencyParser;
+use crate::utils::jsonc;
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+
+pub struct DenoParser;
+
+impl DependencyParser for DenoParser {
+    fn ecosystem_type(&self) -> EcosystemType {
+        EcosystemType::Deno
+    }
+
+    fn supported_files(&self) -> Vec<&'static str> {
+        vec!["deno.json", "deno.jsonc"]
+    }
+
+    /// Also any JSON file, since the import map an `importMap` field points
+    /// to may have any name
+    fn reads_file(&self, file_path: &Path) -> bool {
+        self.can_parse(file_path)
+            || matches!(
+                file_path.extension().and_then(|ext| ext.to_str()),
+                Some("json" | "jsonc")
+            )
+    }
+
+    fn parse_dependencies(&self, file_path: &Path) -> Result<Vec<Dependency>> {
+        let config = read_jsonc(file_path)?;
+
+        // The import map is either inline or in a separate file referenced by `importMap`
+        let import_map = match config.get("imports") {
+            Some(_) => config,
+            None => match config.get("importMap").and_then(|p| p.as_str()) {
+                Some(relative) => {
+                    let dir = file_path.parent().unwrap_or(Path::new("."));
+                    read_jsonc(&dir.join(relative))?
+                }
+                None => config,
+            },
+        };
+
+        let mut deps = Vec::new();
+
+        if let Some(imports) = import_map.get("imports").and_then(|v| v.as_object()) {
+            for (key, target) in imports {
+                let target = match target.as_str() {
+                    Some(target) => target,
+                    None => continue,
+                };
+
+                // Mappings to local files are not dependencies
+                let package = match specifier::parse(target) {
+                    Some(package) => package,
+                    None => continue,
+                };
+
+                // Prefix mappings (`"oak/": "https://deno.land/x/oak@v12/"`) are
+                // imported by their prefix, so drop the trailing slash
+                let name = key.trim_end_matches('/').to_string();
+
+                let mut dep = Dependency::new(
+                    name,
+                    package.version.unwrap_or_else(|| "*".to_string()),
+                    EcosystemType::Deno,
+                    DependencyType::Direct,
+                );
+                if package.name != dep.name {
+                    dep.alias_for = Some(package.name);
+                }
+                deps.push(dep);
+            }
+        }
+
+        Ok(deps)
+    }
+}
+
+fn read_jsonc(file_path: &Path) -> Result<Value> {
+    let content = fs::read_to_string(file_path).map_err(|e| DependencyBlameError::ParseError {
+        file: file_path.display().to_string(),
+        reason: e.to_string(),
+    })?;
+
+    jsonc::parse(&content).map_err(|e| DependencyBlameError::ParseError {
+        file: file_path.display().to_string(),
+        reason: e.to_string(),
+    })
+}
@@ -0,0 +1,53 @@
+use crate::core::dependency::{Dependency, EcosystemType};
+use crate::core::error::Result;
+use crate::ecosystems::deno::specifier;
+use crate::ecosystems::node::find_module_references;
+use crate::ecosystems::traits::{ImportScanner, ImportStatement};
+
+pub struct DenoScanner;
+
+impl ImportScanner for DenoScanner {
+    fn ecosystem_type(&self) -> EcosystemType {
+        EcosystemType::Deno
+    }
+
+    fn file_extensions(&self) -> Vec<&'static str> {
+        vec!["ts", "tsx", "js", "jsx", "mjs", "mts"]
+    }
+
+    fn version(&self) -> u32 {
+        1
+    }
+
+    fn extract_imports(&self, content: &str) -> Result<Vec<String>> {
+        Ok(find_module_references(content)
+            .into_iter()
+            .map(|import| import.module)
+            .collect())
+    }
+
+    fn find_imports(&self, content: &str) -> Result<Vec<ImportStatement>> {
+        Ok(find_module_references(content))
+    }
+
+    fn import_matches(&self, import: &str, dependency_name: &str) -> bool {
+        // Bare specifiers resolve through the import map: exact keys or subpaths
+        import == dependency_name
+            || import
+                .strip_prefix(dependency_name)
+                .map(|rest| rest.starts_with('/'))
+                .unwrap_or(false)
+    }
+
+    fn dependency_matches(&self, import: &str, dependency: &Dependency) -> bool {
+        if self.import_matches(import, &dependency.name) {
+            return true;
+        }
+
+        // Inline `npm:`/`jsr:`/URL specifiers name the real package directly
+        let package = dependency.alias_for.as_ref().unwrap_or(&dependency.name);
+        specifier::parse(import)
+            .map(|inline| &inline.name == package)
+            .unwrap_or(false)
+    }
+}
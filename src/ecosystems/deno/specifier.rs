@@ -0,0 +1,122 @@
+//! Parsing of Deno module specifiers: `jsr:`, `npm:` and remote URLs.
+
+/// The package a specifier refers to, if it can be determined
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackageRef {
+    pub name: String,
+    pub version: Option<String>,
+}
+
+/// Parse `jsr:@std/path@^1/join`, `npm:chalk@5` or a registry URL such as
+/// `https://deno.land/x/oak@v12.6.0/mod.ts` into a package name and version.
+/// Bare and relative specifiers return `None`.
+pub fn parse(specifier: &str) -> Option<PackageRef> {
+    if let Some(spec) = specifier
+        .strip_prefix("jsr:")
+        .or_else(|| specifier.strip_prefix("npm:"))
+    {
+        return Some(parse_package(spec.trim_start_matches('/')));
+    }
+
+    let path = specifier
+        .strip_prefix("https://")
+        .or_else(|| specifier.strip_prefix("http://"))?;
+    let (host, path) = path.split_once('/')?;
+
+    match host {
+        // deno.land/x/name@version/..., deno.land/std@version/...
+        "deno.land" => {
+            let path = path.strip_prefix("x/").unwrap_or(path);
+            Some(parse_package(path))
+        }
+        // CDNs that serve npm packages by name
+        "esm.sh" | "cdn.skypack.dev" | "cdn.jsdelivr.net" | "unpkg.com" => {
+            let path = path.strip_prefix("npm/").unwrap_or(path);
+            Some(parse_package(path))
+        }
+        _ => None,
+    }
+}
+
+/// Parse `name@version/subpath` or `@scope/name@version/subpath`
+fn parse_package(spec: &str) -> PackageRef {
+    let mut segments = spec.splitn(3, '/');
+    let first = segments.next().unwrap_or("");
+    let package = if first.starts_with('@') {
+        match segments.next() {
+            Some(second) => format!("{}/{}", first, second),
+            None => first.to_string(),
+        }
+    } else {
+        first.to_string()
+    };
+
+    // Skip the leading `@` of a scoped name when looking for the version
+    let search_from = usize::from(package.starts_with('@'));
+    match package[search_from..].find('@') {
+        Some(pos) => {
+            let pos = pos + search_from;
+            PackageRef {
+                name: package[..pos].to_string(),
+                version: Some(package[pos + 1..].to_string()),
+            }
+        }
+        None => PackageRef {
+            name: package,
+            version: None,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn package(name: &str, version: Option<&str>) -> Option<PackageRef> {
+        Some(PackageRef {
+            name: name.to_string(),
+            version: version.map(str::to_string),
+        })
+    }
+
+    #[test]
+    fn jsr_and_npm_specifiers() {
+        assert_eq!(
+            parse("jsr:@std/path@^1.0.0/join"),
+            package("@std/path", Some("^1.0.0"))
+        );
+        assert_eq!(parse("jsr:/@std/assert"), package("@std/assert", None));
+        assert_eq!(parse("npm:chalk@5"), package("chalk", Some("5")));
+        assert_eq!(
+            parse("npm:@types/node@20/fs"),
+            package("@types/node", Some("20"))
+        );
+    }
+
+    #[test]
+    fn registry_urls() {
+        assert_eq!(
+            parse("https://deno.land/x/oak@v12.6.0/mod.ts"),
+            package("oak", Some("v12.6.0"))
+        );
+        assert_eq!(
+            parse("https://deno.land/std@0.200.0/path/mod.ts"),
+            package("std", Some("0.200.0"))
+        );
+        assert_eq!(
+            parse("https://esm.sh/preact@10.19.2/hooks"),
+            package("preact", Some("10.19.2"))
+        );
+        assert_eq!(
+            parse("https://cdn.jsdelivr.net/npm/@scope/pkg@1/index.js"),
+            package("@scope/pkg", Some("1"))
+        );
+    }
+
+    #[test]
+    fn other_specifiers_have_no_package() {
+        assert_eq!(parse("./utils.ts"), None);
+        assert_eq!(parse("oak"), None);
+        assert_eq!(parse("https://example.com/lib.ts"), None);
+    }
+}
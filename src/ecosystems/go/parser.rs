@@ -4,6 +4,7 @@ use crate::ecosystems::traits::DependencyParser;
 use regex::Regex;
 use std::fs;
 use std::path::Path;
+use std::sync::OnceLock;
 
 pub struct GoParser;
 
@@ -27,10 +28,14 @@ impl DependencyParser for GoParser {
         let mut in_require_block = false;
 
         // Regex for inline require: require github.com/foo/bar v1.2.3
-        let inline_require_regex = Regex::new(r"^\s*require\s+(\S+)\s+(\S+)")?;
+        static INLINE_REQUIRE: OnceLock<Regex> = OnceLock::new();
+        let inline_require_regex = INLINE_REQUIRE
+            .get_or_init(|| Regex::new(r"^\s*require\s+(\S+)\s+(\S+)").expect("valid regex"));
 
         // Regex for require block entry: github.com/foo/bar v1.2.3
-        let block_entry_regex = Regex::new(r"^\s+(\S+)\s+(\S+)")?;
+        static BLOCK_ENTRY: OnceLock<Regex> = OnceLock::new();
+        let block_entry_regex =
+            BLOCK_ENTRY.get_or_init(|| Regex::new(r"^\s+(\S+)\s+(\S+)").expect("valid regex"));
 
         for line in content.lines() {
             let trimmed = line.trim();
@@ -50,12 +55,12 @@ impl DependencyParser for GoParser {
             // Parse inline require
             if let Some(cap) = inline_require_regex.captures(trimmed) {
                 if let (Some(name), Some(version)) = (cap.get(1), cap.get(2)) {
-                    deps.push(Dependency {
-                        name: name.as_str().to_string(),
-                        version: version.as_str().to_string(),
-                        ecosystem: EcosystemType::Go,
-                        dependency_type: DependencyType::Direct,
-                    });
+                    deps.push(Dependency::new(
+                        name.as_str().to_string(),
+                        version.as_str().to_string(),
+                        EcosystemType::Go,
+                        DependencyType::Direct,
+                    ));
                 }
             }
 
@@ -63,12 +68,12 @@ impl DependencyParser for GoParser {
             if in_require_block {
                 if let Some(cap) = block_entry_regex.captures(trimmed) {
                     if let (Some(name), Some(version)) = (cap.get(1), cap.get(2)) {
-                        deps.push(Dependency {
-                            name: name.as_str().to_string(),
-                            version: version.as_str().to_string(),
-                            ecosystem: EcosystemType::Go,
-                            dependency_type: DependencyType::Direct,
-                        });
+                        deps.push(Dependency::new(
+                            name.as_str().to_string(),
+                            version.as_str().to_string(),
+                            EcosystemType::Go,
+                            DependencyType::Direct,
+                        ));
                     }
                 }
             }
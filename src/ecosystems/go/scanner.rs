@@ -2,6 +2,7 @@ use crate::core::dependency::EcosystemType;
 use crate::core::error::Result;
 use crate::ecosystems::traits::ImportScanner;
 use regex::Regex;
+use std::sync::OnceLock;
 
 pub struct GoScanner;
 
@@ -14,15 +15,23 @@ impl ImportScanner for GoScanner {
         vec!["go"]
     }
 
+    fn version(&self) -> u32 {
+        1
+    }
+
     fn extract_imports(&self, content: &str) -> Result<Vec<String>> {
         let mut imports = Vec::new();
 
         // Single import: import "module"
-        let single_import_regex = Regex::new(r#"(?m)^\s*import\s+"([^"]+)""#)?;
+        static SINGLE_IMPORT: OnceLock<Regex> = OnceLock::new();
+        let single_import_regex = SINGLE_IMPORT
+            .get_or_init(|| Regex::new(r#"(?m)^\s*import\s+"([^"]+)""#).expect("valid regex"));
 
         // Multi-line import block
         let mut in_import_block = false;
-        let import_entry_regex = Regex::new(r#"^\s+"([^"]+)""#)?;
+        static IMPORT_ENTRY: OnceLock<Regex> = OnceLock::new();
+        let import_entry_regex =
+            IMPORT_ENTRY.get_or_init(|| Regex::new(r#"^\s+"([^"]+)""#).expect("valid regex"));
 
         for line in content.lines() {
             let trimmed = line.trim();
@@ -1,3 +1,4 @@
+pub mod deno;
 pub mod go;
 pub mod node;
 pub mod python;
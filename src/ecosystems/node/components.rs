@@ -0,0 +1,179 @@
+use crate::ecosystems::traits::SourceSegment;
+
+/// Extract the contents of every `<script>` block in a Vue or Svelte
+/// single-file component. Line offsets point back into the original file.
+pub fn script_blocks(content: &str) -> Vec<SourceSegment> {
+    let mut segments = Vec::new();
+    let mut search_from = 0;
+
+    while let Some(open) = find_tag(content, search_from, "<script") {
+        let body_start = match content[open..].find('>') {
+            Some(end) => open + end + 1,
+            None => break,
+        };
+        let body_end = content[body_start..]
+            .find("</script>")
+            .map(|end| body_start + end)
+            .unwrap_or(content.len());
+
+        segments.push(segment(content, body_start, body_end));
+        search_from = body_end;
+    }
+
+    segments
+}
+
+/// Extract the `---` frontmatter script and any `<script>` blocks of an Astro component
+pub fn astro_sections(content: &str) -> Vec<SourceSegment> {
+    let mut segments = Vec::new();
+    let mut markup_start = 0;
+
+    if content.trim_start().starts_with("---") {
+        let fence = content.find("---").unwrap_or(0);
+        let body_start = content[fence + 3..]
+            .find('\n')
+            .map(|nl| fence + 3 + nl + 1)
+            .unwrap_or(content.len());
+        let body_end = if content[body_start..].starts_with("---") {
+            body_start
+        } else {
+            content[body_start..]
+                .find("\n---")
+                .map(|end| body_start + end + 1)
+                .unwrap_or(content.len())
+        };
+
+        segments.push(segment(content, body_start, body_end));
+        markup_start = body_end;
+    }
+
+    segments.extend(
+        script_blocks(&content[markup_start..])
+            .into_iter()
+            .map(|mut s| {
+                s.line_offset += content[..markup_start].matches('\n').count();
+                s
+            }),
+    );
+
+    segments
+}
+
+/// Keep only the ESM `import`/`export` blocks of an MDX document.
+///
+/// Every other line is blanked so line numbers still match the original file.
+/// An ESM block runs until the next blank line; fenced code blocks are skipped.
+pub fn mdx_esm(content: &str) -> Vec<SourceSegment> {
+    let mut kept = Vec::new();
+    let mut in_fence = false;
+    let mut in_esm = false;
+
+    for line in content.lines() {
+        let trimmed = line.trim_start();
+
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+            in_esm = false;
+        } else if !in_fence && line.trim().is_empty() {
+            in_esm = false;
+        } else if !in_fence && (line.starts_with("import ") || line.starts_with("export ")) {
+            in_esm = true;
+        }
+
+        kept.push(if in_esm && !in_fence { line } else { "" });
+    }
+
+    vec![SourceSegment {
+        content: kept.join("\n"),
+        line_offset: 0,
+        cell_index: None,
+    }]
+}
+
+/// Find an opening tag such as `<script` that is followed by `>` or whitespace
+fn find_tag(content: &str, from: usize, tag: &str) -> Option<usize> {
+    let mut pos = from;
+    while let Some(found) = content[pos..].find(tag) {
+        let start = pos + found;
+        let after = content[start + tag.len()..].chars().next();
+        if matches!(after, Some(c) if c == '>' || c.is_whitespace()) {
+            return Some(start);
+        }
+        pos = start + tag.len();
+    }
+    None
+}
+
+fn segment(content: &str, start: usize, end: usize) -> SourceSegment {
+    SourceSegment {
+        content: content[start..end].to_string(),
+        line_offset: content[..start].matches('\n').count(),
+        cell_index: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ecosystems::node::find_module_references;
+
+    /// Imports of every segment with their 0-based line in the original file
+    fn imports(segments: &[SourceSegment]) -> Vec<(String, usize)> {
+        segments
+            .iter()
+            .flat_map(|segment| {
+                find_module_references(&segment.content)
+                    .into_iter()
+                    .map(|import| (import.module, import.line + segment.line_offset))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn script_setup_with_attributes() {
+        let vue = "<template>\n  <Chart />\n</template>\n\n<script setup lang=\"ts\">\nimport Chart from 'chart.js'\n</script>\n";
+
+        let segments = script_blocks(vue);
+        assert_eq!(segments.len(), 1);
+        assert_eq!(segments[0].line_offset, 4);
+        assert_eq!(imports(&segments), [("chart.js".to_string(), 5)]);
+    }
+
+    #[test]
+    fn every_script_block_of_a_component() {
+        let vue = "<script>\nimport { defineComponent } from 'vue'\nexport default defineComponent({})\n</script>\n\n<script setup>\nimport dayjs from 'dayjs'\n</script>\n\n<template><p>{{ date }}</p></template>\n";
+
+        let segments = script_blocks(vue);
+        assert_eq!(segments.len(), 2);
+        assert_eq!(
+            imports(&segments),
+            [("vue".to_string(), 1), ("dayjs".to_string(), 6)]
+        );
+    }
+
+    #[test]
+    fn astro_frontmatter_and_scripts() {
+        let astro = "---\nimport Layout from '../layouts/Layout.astro'\nimport { format } from 'date-fns'\n---\n<Layout>\n  <p>{format(new Date(), 'PP')}</p>\n</Layout>\n<script>\n  import confetti from 'canvas-confetti'\n</script>\n";
+
+        let segments = astro_sections(astro);
+        assert_eq!(segments.len(), 2);
+        assert_eq!(segments[0].line_offset, 1);
+        assert_eq!(
+            imports(&segments),
+            [
+                ("../layouts/Layout.astro".to_string(), 1),
+                ("date-fns".to_string(), 2),
+                ("canvas-confetti".to_string(), 8),
+            ]
+        );
+    }
+
+    #[test]
+    fn mdx_imports_after_prose() {
+        let mdx = "# Charts\n\nSome prose that mentions import statements.\n\nimport { Chart } from 'chart.js'\nexport const meta = { title: 'Charts' }\n\n```js\nimport hidden from 'not-a-dependency'\n```\n\n<Chart />\n";
+
+        let segments = mdx_esm(mdx);
+        assert_eq!(segments.len(), 1);
+        assert_eq!(imports(&segments), [("chart.js".to_string(), 4)]);
+    }
+}
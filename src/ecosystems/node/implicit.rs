@@ -0,0 +1,650 @@
+//! Detectors for Node packages that are used without ever being imported:
+//! CLIs run from npm scripts, and plugins/presets named in tool configuration.
+
+use crate::core::dependency::{Dependency, ImportLocation, UsageKind};
+use crate::ecosystems::node::types;
+use crate::utils::jsonc;
+use regex::Regex;
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+use std::sync::OnceLock;
+
+/// Finds usages of a package that do not show up as imports
+pub trait ImplicitUsageDetector: Send + Sync {
+    /// Short name of the detector, for diagnostics
+    fn name(&self) -> &'static str;
+
+    /// Whether `detect` may read this file, which is then written out for
+    /// `--rev` along with the sources
+    fn reads_file(&self, file_path: &Path) -> bool;
+
+    /// Find usages of the dependency in the project rooted at `project_dir`
+    fn detect(&self, project_dir: &Path, dependency: &Dependency) -> Vec<ImportLocation>;
+}
+
+/// The detectors the Node adapter runs by default
+pub fn default_detectors() -> Vec<Box<dyn ImplicitUsageDetector>> {
+    vec![
+        Box::new(ScriptsDetector),
+        Box::new(ConfigFileDetector),
+        Box::new(TsconfigTypesDetector),
+    ]
+}
+
+fn file_name(path: &Path) -> &str {
+    path.file_name().and_then(|n| n.to_str()).unwrap_or("")
+}
+
+/// CLIs invoked from package.json `scripts`
+pub struct ScriptsDetector;
+
+impl ImplicitUsageDetector for ScriptsDetector {
+    fn name(&self) -> &'static str {
+        "scripts"
+    }
+
+    fn reads_file(&self, file_path: &Path) -> bool {
+        matches!(
+            file_name(file_path),
+            "package.json" | "package-lock.json" | "npm-shrinkwrap.json"
+        )
+    }
+
+    fn detect(&self, project_dir: &Path, dependency: &Dependency) -> Vec<ImportLocation> {
+        let path = project_dir.join("package.json");
+        let content = match fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(_) => return Vec::new(),
+        };
+        let scripts = match serde_json::from_str::<Value>(&content)
+            .ok()
+            .and_then(|v| v.get("scripts").cloned())
+        {
+            Some(Value::Object(scripts)) => scripts,
+            _ => return Vec::new(),
+        };
+
+        let bins = bin_names(project_dir, &dependency.name);
+
+        scripts
+            .iter()
+            .filter(|(_, command)| {
+                command
+                    .as_str()
+                    .map(|c| script_words(c).any(|word| bins.iter().any(|b| b == word)))
+                    .unwrap_or(false)
+            })
+            .map(|(name, _)| {
+                let needle = format!("\"{}\"", name);
+                config_location(
+                    &path,
+                    &content,
+                    &needle,
+                    Some("\"scripts\""),
+                    UsageKind::Script,
+                )
+            })
+            .collect()
+    }
+}
+
+/// Executable names a package installs: from its `bin` field in
+/// node_modules, else as recorded in the npm lockfile, else from
+/// [`KNOWN_BINS`]. Falls back to the unscoped package name.
+fn bin_names(project_dir: &Path, package: &str) -> Vec<String> {
+    let manifest = project_dir
+        .join("node_modules")
+        .join(package)
+        .join("package.json");
+    let unscoped = package.rsplit('/').next().unwrap_or(package);
+
+    let installed = fs::read_to_string(manifest)
+        .ok()
+        .and_then(|content| serde_json::from_str::<Value>(&content).ok())
+        .and_then(|v| v.get("bin").cloned());
+    if let Some(bins) = installed
+        .or_else(|| lockfile_bin(project_dir, package))
+        .and_then(|bin| bin_keys(&bin, unscoped))
+    {
+        return bins;
+    }
+
+    match KNOWN_BINS.iter().find(|(name, _)| *name == package) {
+        Some((_, bins)) => bins.iter().map(|b| b.to_string()).collect(),
+        None => vec![unscoped.to_string()],
+    }
+}
+
+/// The names in a `bin` field, which is a single path for a command named
+/// after the package, or an object of commands
+fn bin_keys(bin: &Value, unscoped: &str) -> Option<Vec<String>> {
+    match bin {
+        Value::String(_) => Some(vec![unscoped.to_string()]),
+        Value::Object(bins) if !bins.is_empty() => Some(bins.keys().cloned().collect()),
+        _ => None,
+    }
+}
+
+/// The `bin` field npm recorded for a package in the closest
+/// `package-lock.json` or `npm-shrinkwrap.json`, looking up to the
+/// repository root for workspace members
+fn lockfile_bin(project_dir: &Path, package: &str) -> Option<Value> {
+    for dir in project_dir.ancestors() {
+        for file in ["npm-shrinkwrap.json", "package-lock.json"] {
+            let content = match fs::read_to_string(dir.join(file)) {
+                Ok(content) => content,
+                Err(_) => continue,
+            };
+            return serde_json::from_str::<Value>(&content)
+                .ok()?
+                .get("packages")?
+                .get(format!("node_modules/{}", package))?
+                .get("bin")
+                .cloned();
+        }
+        if dir.join(".git").exists() {
+            break;
+        }
+    }
+    None
+}
+
+/// Commands of popular packages that are not named after the package, for
+/// when neither node_modules nor a lockfile say
+const KNOWN_BINS: &[(&str, &[&str])] = &[
+    ("typescript", &["tsc", "tsserver"]),
+    ("@angular/cli", &["ng"]),
+    ("@babel/cli", &["babel", "babel-external-helpers"]),
+    ("@babel/node", &["babel-node"]),
+    ("@biomejs/biome", &["biome"]),
+    ("@changesets/cli", &["changeset"]),
+    ("@commitlint/cli", &["commitlint"]),
+    ("@cucumber/cucumber", &["cucumber-js"]),
+    ("@electron-forge/cli", &["electron-forge"]),
+    ("@graphql-codegen/cli", &["graphql-codegen", "gql-gen"]),
+    ("@nestjs/cli", &["nest"]),
+    ("@playwright/test", &["playwright"]),
+    ("@remix-run/dev", &["remix"]),
+    ("@sentry/cli", &["sentry-cli"]),
+    ("@sveltejs/kit", &["svelte-kit"]),
+    ("@swc/cli", &["swc", "swcx", "spack"]),
+    ("@tauri-apps/cli", &["tauri"]),
+    ("@vscode/vsce", &["vsce"]),
+    ("@vue/cli-service", &["vue-cli-service"]),
+    ("aws-cdk", &["cdk"]),
+    ("concurrently", &["concurrently", "conc"]),
+    ("cross-env", &["cross-env", "cross-env-shell"]),
+    ("firebase-tools", &["firebase"]),
+    ("less", &["lessc"]),
+    ("markdownlint-cli", &["markdownlint"]),
+    ("mocha", &["mocha", "_mocha"]),
+    ("netlify-cli", &["netlify", "ntl"]),
+    ("npm-run-all", &["npm-run-all", "run-p", "run-s"]),
+    ("npm-run-all2", &["npm-run-all", "run-p", "run-s"]),
+    ("nuxt", &["nuxi", "nuxt"]),
+    ("pm2", &["pm2", "pm2-dev", "pm2-runtime"]),
+    ("postcss-cli", &["postcss"]),
+    ("protobufjs-cli", &["pbjs", "pbts"]),
+    ("sequelize-cli", &["sequelize", "sequelize-cli"]),
+    ("serverless", &["serverless", "sls"]),
+    ("ts-node", &["ts-node", "ts-node-esm", "ts-script"]),
+    ("webpack-cli", &["webpack-cli", "webpack"]),
+];
+
+/// Split a script into shell words, dropping operators and `VAR=value` assignments
+fn script_words(command: &str) -> impl Iterator<Item = &str> {
+    command
+        .split(|c: char| c.is_whitespace() || matches!(c, '&' | '|' | ';' | '(' | ')'))
+        .filter(|word| !word.is_empty() && !word.contains('='))
+}
+
+/// Config files that name plugins, presets, parsers and transformers
+const CONFIG_FILES: &[&str] = &[
+    ".eslintrc",
+    ".eslintrc.js",
+    ".eslintrc.cjs",
+    ".eslintrc.json",
+    ".eslintrc.yml",
+    ".eslintrc.yaml",
+    "eslint.config.js",
+    "eslint.config.mjs",
+    "eslint.config.cjs",
+    "eslint.config.ts",
+    "eslint.config.mts",
+    "eslint.config.cts",
+    ".babelrc",
+    ".babelrc.json",
+    ".babelrc.js",
+    ".babelrc.cjs",
+    ".babelrc.mjs",
+    "babel.config.js",
+    "babel.config.cjs",
+    "babel.config.mjs",
+    "babel.config.json",
+    "babel.config.cts",
+    "jest.config.js",
+    "jest.config.cjs",
+    "jest.config.mjs",
+    "jest.config.ts",
+    "jest.config.json",
+    "postcss.config.js",
+    "postcss.config.cjs",
+    "postcss.config.mjs",
+    "postcss.config.ts",
+    ".postcssrc",
+    ".postcssrc.json",
+    ".postcssrc.yml",
+    ".postcssrc.yaml",
+    ".postcssrc.js",
+    ".postcssrc.cjs",
+    ".postcssrc.mjs",
+    ".prettierrc",
+    ".prettierrc.json",
+    ".prettierrc.json5",
+    ".prettierrc.yml",
+    ".prettierrc.yaml",
+    ".prettierrc.toml",
+    ".prettierrc.js",
+    ".prettierrc.cjs",
+    ".prettierrc.mjs",
+    "prettier.config.js",
+    "prettier.config.cjs",
+    "prettier.config.mjs",
+    "tailwind.config.js",
+    "tailwind.config.cjs",
+    "tailwind.config.mjs",
+    "tailwind.config.ts",
+    "tsconfig.json",
+];
+
+/// package.json fields that tools read in place of a config file
+const PACKAGE_JSON_CONFIG_KEYS: &[&str] = &[
+    "eslintConfig",
+    "babel",
+    "jest",
+    "postcss",
+    "prettier",
+    "browserslist",
+];
+
+/// Packages named in ESLint, Babel, Jest, PostCSS, Prettier, Tailwind and
+/// TypeScript configuration, including the short forms those tools accept,
+/// whether in their own files or in the package.json fields they read
+pub struct ConfigFileDetector;
+
+impl ImplicitUsageDetector for ConfigFileDetector {
+    fn name(&self) -> &'static str {
+        "config-files"
+    }
+
+    fn reads_file(&self, file_path: &Path) -> bool {
+        let file_name = file_name(file_path);
+        file_name == "package.json" || CONFIG_FILES.contains(&file_name)
+    }
+
+    fn detect(&self, project_dir: &Path, dependency: &Dependency) -> Vec<ImportLocation> {
+        let short_names = short_names(&dependency.name);
+        static QUOTED: OnceLock<Regex> = OnceLock::new();
+        static LIST_ITEM: OnceLock<Regex> = OnceLock::new();
+        static BARE_KEY: OnceLock<Regex> = OnceLock::new();
+        let quoted =
+            QUOTED.get_or_init(|| Regex::new(r#"["']([^"'\n]+)["']"#).expect("valid regex"));
+        let list_item =
+            LIST_ITEM.get_or_init(|| Regex::new(r"^\s*-\s*([^\s#]+)\s*$").expect("valid regex"));
+        let bare_key =
+            BARE_KEY.get_or_init(|| Regex::new(r"^\s*([@\w][\w@/.-]*)\s*:").expect("valid regex"));
+
+        let mut locations = Vec::new();
+
+        for file in CONFIG_FILES {
+            let path = project_dir.join(file);
+            let content = match fs::read_to_string(&path) {
+                Ok(content) => content,
+                Err(_) => continue,
+            };
+
+            for (i, line) in content.lines().enumerate() {
+                let referenced = quoted
+                    .captures_iter(line)
+                    .chain(list_item.captures_iter(line))
+                    .filter_map(|cap| cap.get(1))
+                    .any(|value| names_package(value.as_str(), &dependency.name, &short_names))
+                    // Bare keys (`autoprefixer: {}`) are too generic for short names
+                    || bare_key
+                        .captures(line)
+                        .and_then(|cap| cap.get(1))
+                        .map(|key| key.as_str() == dependency.name)
+                        .unwrap_or(false);
+
+                if referenced {
+                    locations.push(ImportLocation {
+                        file_path: path.clone(),
+                        line_number: i + 1,
+                        line_content: line.trim().to_string(),
+                        cell_index: None,
+                        kind: UsageKind::Config,
+                        workspace: None,
+                        is_test: false,
+                    });
+                }
+            }
+        }
+
+        locations.extend(package_json_config(project_dir, dependency, &short_names));
+        locations
+    }
+}
+
+/// References to the dependency in the tool configuration fields of the
+/// project's package.json, one per field
+fn package_json_config(
+    project_dir: &Path,
+    dependency: &Dependency,
+    short_names: &[String],
+) -> Vec<ImportLocation> {
+    let path = project_dir.join("package.json");
+    let content = match fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(_) => return Vec::new(),
+    };
+    let package_json = match serde_json::from_str::<Value>(&content) {
+        Ok(value) => value,
+        Err(_) => return Vec::new(),
+    };
+
+    let mut locations = Vec::new();
+    for key in PACKAGE_JSON_CONFIG_KEYS {
+        let config = match package_json.get(key) {
+            Some(config) => config,
+            None => continue,
+        };
+        let mut values = Vec::new();
+        config_values(config, &mut values);
+        // browserslist shares configs as `extends <package>` queries
+        let reference = values.into_iter().find(|(value, is_key)| {
+            let value = value.strip_prefix("extends ").unwrap_or(value);
+            match is_key {
+                // As for bare keys in config files, only the full name
+                true => *value == dependency.name,
+                false => names_package(value, &dependency.name, short_names),
+            }
+        });
+        if let Some((value, is_key)) = reference {
+            let needle = match is_key {
+                true => format!("\"{}\":", value),
+                false => format!("\"{}\"", value),
+            };
+            let after = format!("\"{}\"", key);
+            locations.push(config_location(
+                &path,
+                &content,
+                &needle,
+                Some(&after),
+                UsageKind::Config,
+            ));
+        }
+    }
+    locations
+}
+
+/// Every string in a JSON value, and every object key, which names a
+/// plugin in configs such as `"plugins": { "autoprefixer": {} }`
+fn config_values<'a>(value: &'a Value, values: &mut Vec<(&'a str, bool)>) {
+    match value {
+        Value::String(value) => values.push((value, false)),
+        Value::Array(items) => items.iter().for_each(|item| config_values(item, values)),
+        Value::Object(fields) => {
+            for (key, value) in fields {
+                values.push((key, true));
+                config_values(value, values);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Names config files may use instead of the full package name, following
+/// the ESLint and Babel plugin naming conventions
+fn short_names(package: &str) -> Vec<String> {
+    let (scope, name) = match package.split_once('/') {
+        Some((scope, name)) if scope.starts_with('@') => (Some(scope), name),
+        _ => (None, package),
+    };
+
+    let prefixes = [
+        "eslint-plugin",
+        "eslint-config",
+        "babel-plugin",
+        "babel-preset",
+        "plugin",
+        "preset",
+    ];
+
+    let mut names = Vec::new();
+    for prefix in prefixes {
+        // `@scope/eslint-plugin` is referred to as `@scope`
+        if name == prefix {
+            if let Some(scope) = scope {
+                names.push(scope.to_string());
+            }
+            continue;
+        }
+        // The bare `plugin-`/`preset-` prefixes are only shortened inside a scope
+        if scope.is_none() && !prefix.contains('-') {
+            continue;
+        }
+        if let Some(short) = name.strip_prefix(prefix).and_then(|s| s.strip_prefix('-')) {
+            names.push(match scope {
+                Some(scope) => format!("{}/{}", scope, short),
+                None => short.to_string(),
+            });
+        }
+    }
+    names
+}
+
+/// Check if a config value names the package: exactly, as a path inside it
+/// (`@tsconfig/node18/tsconfig.json`), or by a short name (`plugin:react/recommended`)
+fn names_package(value: &str, package: &str, short_names: &[String]) -> bool {
+    let value = value.strip_prefix("plugin:").unwrap_or(value);
+    let refers_to = |name: &str| {
+        value == name
+            || value
+                .strip_prefix(name)
+                .map(|rest| rest.starts_with('/'))
+                .unwrap_or(false)
+    };
+    refers_to(package) || short_names.iter().any(|short| refers_to(short))
+}
+
+/// `@types/*` packages listed in tsconfig `compilerOptions.types`
+pub struct TsconfigTypesDetector;
+
+impl ImplicitUsageDetector for TsconfigTypesDetector {
+    fn name(&self) -> &'static str {
+        "tsconfig-types"
+    }
+
+    fn reads_file(&self, file_path: &Path) -> bool {
+        file_name(file_path) == "tsconfig.json"
+    }
+
+    fn detect(&self, project_dir: &Path, dependency: &Dependency) -> Vec<ImportLocation> {
+        let reference = match types::types_reference_name(&dependency.name) {
+            Some(reference) => reference,
+            None => return Vec::new(),
+        };
+
+        let path = project_dir.join("tsconfig.json");
+        let content = match fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(_) => return Vec::new(),
+        };
+        let tsconfig = match jsonc::parse(&content) {
+            Ok(value) => value,
+            Err(_) => return Vec::new(),
+        };
+
+        let listed = tsconfig
+            .get("compilerOptions")
+            .and_then(|o| o.get("types"))
+            .and_then(|t| t.as_array())
+            .map(|types| types.iter().any(|t| t.as_str() == Some(reference)))
+            .unwrap_or(false);
+
+        if !listed {
+            return Vec::new();
+        }
+
+        let needle = format!("\"{}\"", reference);
+        vec![config_location(
+            &path,
+            &content,
+            &needle,
+            Some("\"types\""),
+            UsageKind::Config,
+        )]
+    }
+}
+
+/// Build a location for the first line containing `needle`, optionally only
+/// looking after the first line that contains `after`
+fn config_location(
+    path: &Path,
+    content: &str,
+    needle: &str,
+    after: Option<&str>,
+    kind: UsageKind,
+) -> ImportLocation {
+    let start = after
+        .and_then(|marker| content.lines().position(|l| l.contains(marker)))
+        .unwrap_or(0);
+
+    let (line_number, line_content) = content
+        .lines()
+        .enumerate()
+        .skip(start)
+        .find(|(_, line)| line.contains(needle))
+        .map(|(i, line)| (i + 1, line.trim().to_string()))
+        .unwrap_or((1, String::new()));
+
+    ImportLocation {
+        file_path: path.to_path_buf(),
+        line_number,
+        line_content,
+        cell_index: None,
+        kind,
+        workspace: None,
+        is_test: false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::dependency::{DependencyType, EcosystemType};
+
+    fn dependency(name: &str) -> Dependency {
+        Dependency::new(
+            name.to_string(),
+            "1".to_string(),
+            EcosystemType::Node,
+            DependencyType::Dev,
+        )
+    }
+
+    fn project(package_json: &str) -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("package.json"), package_json).unwrap();
+        dir
+    }
+
+    #[test]
+    fn known_bin_without_node_modules() {
+        let dir = project(r#"{"scripts": {"build": "tsc -p .", "lint": "eslint src"}}"#);
+        let found = ScriptsDetector.detect(dir.path(), &dependency("typescript"));
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].kind, UsageKind::Script);
+        assert!(found[0].line_content.contains("\"build\""));
+    }
+
+    #[test]
+    fn bin_from_lockfile() {
+        let dir = project(r#"{"scripts": {"check": "NODE_ENV=test vue-tsc --noEmit"}}"#);
+        fs::write(
+            dir.path().join("package-lock.json"),
+            r#"{"packages": {"node_modules/vue-tsc": {"bin": {"vue-tsc": "bin/vue-tsc.js"}}}}"#,
+        )
+        .unwrap();
+        assert_eq!(bin_names(dir.path(), "vue-tsc"), vec!["vue-tsc"]);
+        assert_eq!(
+            ScriptsDetector
+                .detect(dir.path(), &dependency("vue-tsc"))
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn installed_bin_wins() {
+        let dir = project("{}");
+        let installed = dir.path().join("node_modules/@scope/tool");
+        fs::create_dir_all(&installed).unwrap();
+        fs::write(
+            installed.join("package.json"),
+            r#"{"bin": {"do-it": "cli.js"}}"#,
+        )
+        .unwrap();
+        assert_eq!(bin_names(dir.path(), "@scope/tool"), vec!["do-it"]);
+        assert_eq!(bin_names(dir.path(), "@scope/other"), vec!["other"]);
+    }
+
+    #[test]
+    fn script_words_skip_assignments_and_operators() {
+        let words: Vec<&str> = script_words("CI=1 jest && (tsc|tee log)").collect();
+        assert_eq!(words, vec!["jest", "tsc", "tee", "log"]);
+    }
+
+    #[test]
+    fn package_json_config_fields() {
+        let dir = project(
+            r#"{
+  "name": "app",
+  "eslintConfig": {
+    "extends": ["plugin:react/recommended"]
+  },
+  "prettier": "@company/prettier-config",
+  "postcss": {
+    "plugins": {"autoprefixer": {}}
+  },
+  "browserslist": ["extends @company/browserslist-config"]
+}"#,
+        );
+        let found = |name| ConfigFileDetector.detect(dir.path(), &dependency(name));
+
+        let react = found("eslint-plugin-react");
+        assert_eq!(react.len(), 1);
+        assert_eq!(react[0].kind, UsageKind::Config);
+        assert_eq!(react[0].line_number, 4);
+        assert_eq!(found("@company/prettier-config")[0].line_number, 6);
+        assert_eq!(found("autoprefixer")[0].line_number, 8);
+        assert_eq!(found("@company/browserslist-config")[0].line_number, 10);
+        assert!(found("jest").is_empty());
+    }
+
+    #[test]
+    fn yaml_config_files() {
+        let dir = project("{}");
+        fs::write(
+            dir.path().join(".prettierrc.yaml"),
+            "plugins:\n  - prettier-plugin-tailwindcss\n",
+        )
+        .unwrap();
+        assert!(ConfigFileDetector.reads_file(&dir.path().join(".prettierrc.yml")));
+        let found =
+            ConfigFileDetector.detect(dir.path(), &dependency("prettier-plugin-tailwindcss"));
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].line_number, 2);
+    }
+}
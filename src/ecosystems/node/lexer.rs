@@ -0,0 +1,322 @@
+//! A small JavaScript/TypeScript tokenizer.
+//!
+//! It only understands enough of the language to find module specifiers:
+//! comments, string/template/regex literals are recognised so that import-like
+//! text inside them is never reported, and everything else is reduced to
+//! identifiers and punctuation.
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TokenKind {
+    /// Identifier, keyword or number
+    Ident(String),
+    /// Single- or double-quoted string literal (without quotes)
+    Str(String),
+    /// Template or regex literal; contents are never inspected
+    Literal,
+    /// `/// <reference types="..." />` directive
+    TypeReference(String),
+    Punct(char),
+}
+
+#[derive(Debug, Clone)]
+pub struct Token {
+    pub kind: TokenKind,
+    /// 0-based line the token starts on
+    pub line: usize,
+}
+
+pub fn tokenize(source: &str) -> Vec<Token> {
+    Lexer {
+        chars: source.chars().collect(),
+        pos: 0,
+        line: 0,
+        tokens: Vec::new(),
+    }
+    .run()
+}
+
+struct Lexer {
+    chars: Vec<char>,
+    pos: usize,
+    line: usize,
+    tokens: Vec<Token>,
+}
+
+impl Lexer {
+    fn run(mut self) -> Vec<Token> {
+        while let Some(c) = self.peek(0) {
+            let line = self.line;
+            match c {
+                '\n' => {
+                    self.line += 1;
+                    self.pos += 1;
+                }
+                c if c.is_whitespace() => self.pos += 1,
+                '/' if self.peek(1) == Some('/') => self.line_comment(),
+                '/' if self.peek(1) == Some('*') => self.block_comment(),
+                '/' if self.regex_allowed() => {
+                    self.regex();
+                    self.push(TokenKind::Literal, line);
+                }
+                '\'' | '"' => {
+                    let value = self.string(c);
+                    self.push(TokenKind::Str(value), line);
+                }
+                '`' => {
+                    self.template();
+                    self.push(TokenKind::Literal, line);
+                }
+                c if is_ident_char(c) => {
+                    let start = self.pos;
+                    while self.peek(0).is_some_and(is_ident_char) {
+                        self.pos += 1;
+                    }
+                    let ident = self.chars[start..self.pos].iter().collect();
+                    self.push(TokenKind::Ident(ident), line);
+                }
+                c => {
+                    self.pos += 1;
+                    self.push(TokenKind::Punct(c), line);
+                }
+            }
+        }
+        self.tokens
+    }
+
+    fn peek(&self, offset: usize) -> Option<char> {
+        self.chars.get(self.pos + offset).copied()
+    }
+
+    fn push(&mut self, kind: TokenKind, line: usize) {
+        self.tokens.push(Token { kind, line });
+    }
+
+    fn line_comment(&mut self) {
+        let line = self.line;
+        let start = self.pos;
+        while self.peek(0).is_some_and(|c| c != '\n') {
+            self.pos += 1;
+        }
+        let comment: String = self.chars[start..self.pos].iter().collect();
+        if let Some(types) = reference_types(&comment) {
+            self.push(TokenKind::TypeReference(types), line);
+        }
+    }
+
+    fn block_comment(&mut self) {
+        self.pos += 2;
+        while let Some(c) = self.peek(0) {
+            if c == '*' && self.peek(1) == Some('/') {
+                self.pos += 2;
+                return;
+            }
+            if c == '\n' {
+                self.line += 1;
+            }
+            self.pos += 1;
+        }
+    }
+
+    /// Strings cannot span lines, so an unterminated quote (e.g. an
+    /// apostrophe in JSX text) only swallows the rest of its line.
+    fn string(&mut self, quote: char) -> String {
+        self.pos += 1;
+        let mut value = String::new();
+        while let Some(c) = self.peek(0) {
+            match c {
+                '\\' => {
+                    if self.peek(1) == Some('\n') {
+                        self.line += 1;
+                    }
+                    self.pos += 2;
+                }
+                '\n' => break,
+                c if c == quote => {
+                    self.pos += 1;
+                    break;
+                }
+                c => {
+                    value.push(c);
+                    self.pos += 1;
+                }
+            }
+        }
+        value
+    }
+
+    fn template(&mut self) {
+        self.pos += 1;
+        let mut depth = 0usize;
+        while let Some(c) = self.peek(0) {
+            match c {
+                '\\' => {
+                    if self.peek(1) == Some('\n') {
+                        self.line += 1;
+                    }
+                    self.pos += 1;
+                }
+                '\n' => self.line += 1,
+                '$' if self.peek(1) == Some('{') => {
+                    depth += 1;
+                    self.pos += 1;
+                }
+                '{' if depth > 0 => depth += 1,
+                '}' if depth > 0 => depth -= 1,
+                '`' if depth == 0 => {
+                    self.pos += 1;
+                    return;
+                }
+                _ => {}
+            }
+            self.pos += 1;
+        }
+    }
+
+    fn regex(&mut self) {
+        self.pos += 1;
+        let mut in_class = false;
+        while let Some(c) = self.peek(0) {
+            match c {
+                '\\' => self.pos += 1,
+                '\n' => return,
+                '[' => in_class = true,
+                ']' => in_class = false,
+                '/' if !in_class => {
+                    self.pos += 1;
+                    break;
+                }
+                _ => {}
+            }
+            self.pos += 1;
+        }
+        // Flags
+        while self.peek(0).is_some_and(is_ident_char) {
+            self.pos += 1;
+        }
+    }
+
+    /// A `/` starts a regex unless it follows something that ends an expression
+    fn regex_allowed(&self) -> bool {
+        match self.tokens.last().map(|t| &t.kind) {
+            None => true,
+            Some(TokenKind::Ident(word)) => matches!(
+                word.as_str(),
+                "return"
+                    | "typeof"
+                    | "instanceof"
+                    | "in"
+                    | "of"
+                    | "new"
+                    | "delete"
+                    | "void"
+                    | "throw"
+                    | "case"
+                    | "do"
+                    | "else"
+                    | "yield"
+                    | "await"
+            ),
+            Some(TokenKind::Str(_)) | Some(TokenKind::Literal) => false,
+            Some(TokenKind::Punct(c)) => !matches!(c, ')' | ']' | '}'),
+            Some(TokenKind::TypeReference(_)) => true,
+        }
+    }
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '$'
+}
+
+/// Parse `/// <reference types="pkg" />` out of a line comment
+fn reference_types(comment: &str) -> Option<String> {
+    let directive = comment.strip_prefix("///")?.trim_start();
+    let rest = directive.strip_prefix("<reference")?;
+    let attr = rest.find("types=")?;
+    let value = &rest[attr + "types=".len()..];
+    let quote = value.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+    let value = &value[1..];
+    let end = value.find(quote)?;
+    Some(value[..end].to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(source: &str) -> Vec<TokenKind> {
+        tokenize(source).into_iter().map(|t| t.kind).collect()
+    }
+
+    fn strings(source: &str) -> Vec<String> {
+        kinds(source)
+            .into_iter()
+            .filter_map(|kind| match kind {
+                TokenKind::Str(value) => Some(value),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn strings_keep_their_content_without_quotes() {
+        assert_eq!(strings(r#"a("x", 'y')"#), ["x", "y"]);
+        // Escapes are dropped together with the escaped character
+        assert_eq!(strings(r#"'it\'s' "say \"hi\"""#), ["its", "say hi"]);
+    }
+
+    #[test]
+    fn unterminated_string_ends_at_the_line() {
+        let tokens = tokenize("<p>Don't</p>\nimport 'x'");
+        let last = tokens.last().unwrap();
+        assert_eq!(last.kind, TokenKind::Str("x".to_string()));
+        assert_eq!(last.line, 1);
+    }
+
+    #[test]
+    fn template_literals_are_opaque() {
+        let source = "const s = `import x from 'y' ${a + `'z'`}`;\nrequire('w')";
+        assert_eq!(strings(source), ["w"]);
+        assert!(kinds(source).contains(&TokenKind::Literal));
+        assert_eq!(tokenize(source).last().unwrap().line, 1);
+    }
+
+    #[test]
+    fn comments_are_skipped() {
+        let source = "// import 'a'\n/* require('b')\n */ x";
+        assert!(strings(source).is_empty());
+        let tokens = tokenize(source);
+        assert_eq!(tokens.len(), 1);
+        assert_eq!(tokens[0].line, 2);
+    }
+
+    #[test]
+    fn reference_directive_is_a_token() {
+        assert_eq!(
+            kinds(r#"/// <reference types="node" />"#),
+            [TokenKind::TypeReference("node".to_string())]
+        );
+        assert!(kinds(r#"// <reference types="node" />"#).is_empty());
+    }
+
+    #[test]
+    fn regex_literals_are_opaque() {
+        assert!(strings(r#"const re = /import 'a'/g;"#).is_empty());
+        assert_eq!(strings(r#"return /"/.test(s) ? 'x' : 'y'"#), ["x", "y"]);
+        assert!(strings(r"if (/[/']/.test(c)) {}").is_empty());
+    }
+
+    #[test]
+    fn division_is_not_a_regex() {
+        assert_eq!(
+            kinds("a / b / 'c'"),
+            [
+                TokenKind::Ident("a".to_string()),
+                TokenKind::Punct('/'),
+                TokenKind::Ident("b".to_string()),
+                TokenKind::Punct('/'),
+                TokenKind::Str("c".to_string()),
+            ]
+        );
+        assert_eq!(strings("(x) / 2 + f('y') / 3"), ["y"]);
+    }
+}
@@ -1,9 +1,20 @@
+mod components;
+mod implicit;
+mod lexer;
 mod parser;
 mod scanner;
+mod types;
+mod workspaces;
 
 use crate::core::dependency::EcosystemType;
 use crate::ecosystems::traits::{DependencyParser, EcosystemAdapter, ImportScanner};
 
+pub(crate) use scanner::find_module_references;
+
+pub use implicit::{
+    ConfigFileDetector, ImplicitUsageDetector, ScriptsDetector, TsconfigTypesDetector,
+};
+
 pub struct NodeAdapter {
     parser: parser::NodeParser,
     scanner: scanner::NodeScanner,
@@ -19,9 +30,15 @@ impl NodeAdapter {
     pub fn new() -> Self {
         Self {
             parser: parser::NodeParser,
-            scanner: scanner::NodeScanner,
+            scanner: scanner::NodeScanner::new(implicit::default_detectors()),
         }
     }
+
+    /// Register an additional detector for packages used without being imported
+    pub fn with_detector(mut self, detector: Box<dyn ImplicitUsageDetector>) -> Self {
+        self.scanner.add_detector(detector);
+        self
+    }
 }
 
 impl EcosystemAdapter for NodeAdapter {
@@ -1,6 +1,9 @@
-use crate::core::dependency::{Dependency, DependencyType, EcosystemType};
+use crate::core::dependency::{
+    Dependency, DependencyOverride, DependencyType, EcosystemType, TypedPackage,
+};
 use crate::core::error::{DependencyBlameError, Result};
-use crate::ecosystems::traits::DependencyParser;
+use crate::ecosystems::node::{types, workspaces};
+use crate::ecosystems::traits::{DependencyParser, Lockfile, WorkspaceMember};
 use serde_json::Value;
 use std::fs;
 use std::path::Path;
@@ -16,6 +19,16 @@ impl DependencyParser for NodeParser {
         vec!["package.json"]
     }
 
+    /// Also lockfiles and `pnpm-workspace.yaml`
+    fn reads_file(&self, file_path: &Path) -> bool {
+        let file_name = file_path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+        self.can_parse(file_path)
+            || matches!(
+                file_name,
+                "package-lock.json" | "npm-shrinkwrap.json" | "pnpm-workspace.yaml"
+            )
+    }
+
     fn parse_dependencies(&self, file_path: &Path) -> Result<Vec<Dependency>> {
         let content =
             fs::read_to_string(file_path).map_err(|e| DependencyBlameError::ParseError {
@@ -35,12 +48,12 @@ impl DependencyParser for NodeParser {
         if let Some(dependencies) = package_json.get("dependencies").and_then(|v| v.as_object()) {
             for (name, value) in dependencies {
                 let version = value.as_str().unwrap_or("*").to_string();
-                deps.push(Dependency {
-                    name: name.clone(),
+                deps.push(Dependency::new(
+                    name.clone(),
                     version,
-                    ecosystem: EcosystemType::Node,
-                    dependency_type: DependencyType::Direct,
-                });
+                    EcosystemType::Node,
+                    DependencyType::Direct,
+                ));
             }
         }
 
@@ -51,12 +64,12 @@ impl DependencyParser for NodeParser {
         {
             for (name, value) in dev_dependencies {
                 let version = value.as_str().unwrap_or("*").to_string();
-                deps.push(Dependency {
-                    name: name.clone(),
+                deps.push(Dependency::new(
+                    name.clone(),
                     version,
-                    ecosystem: EcosystemType::Node,
-                    dependency_type: DependencyType::Dev,
-                });
+                    EcosystemType::Node,
+                    DependencyType::Dev,
+                ));
             }
         }
 
@@ -67,12 +80,22 @@ impl DependencyParser for NodeParser {
         {
             for (name, value) in peer_dependencies {
                 let version = value.as_str().unwrap_or("*").to_string();
-                deps.push(Dependency {
-                    name: name.clone(),
+                let optional = package_json
+                    .get("peerDependenciesMeta")
+                    .and_then(|meta| meta.get(name))
+                    .and_then(|meta| meta.get("optional"))
+                    .and_then(|o| o.as_bool())
+                    .unwrap_or(false);
+                deps.push(Dependency::new(
+                    name.clone(),
                     version,
-                    ecosystem: EcosystemType::Node,
-                    dependency_type: DependencyType::Peer,
-                });
+                    EcosystemType::Node,
+                    if optional {
+                        DependencyType::OptionalPeer
+                    } else {
+                        DependencyType::Peer
+                    },
+                ));
             }
         }
 
@@ -83,15 +106,289 @@ impl DependencyParser for NodeParser {
         {
             for (name, value) in optional_dependencies {
                 let version = value.as_str().unwrap_or("*").to_string();
-                deps.push(Dependency {
-                    name: name.clone(),
+                deps.push(Dependency::new(
+                    name.clone(),
                     version,
-                    ecosystem: EcosystemType::Node,
-                    dependency_type: DependencyType::Optional,
-                });
+                    EcosystemType::Node,
+                    DependencyType::Optional,
+                ));
+            }
+        }
+
+        // bundledDependencies lists names, or `true` to bundle every dependency
+        let bundled = package_json
+            .get("bundledDependencies")
+            .or_else(|| package_json.get("bundleDependencies"));
+
+        for dep in &mut deps {
+            // `workspace:` protocol links point at packages in the same repository
+            dep.is_internal = dep.version.starts_with("workspace:");
+
+            // `"lodash-es": "npm:lodash@^4"` installs lodash under a local name
+            if let Some(spec) = dep.version.strip_prefix("npm:") {
+                let (package, version) = split_package_version(spec);
+                dep.alias_for = Some(package.to_string());
+                dep.version = version.unwrap_or("*").to_string();
             }
+
+            dep.is_bundled = match bundled {
+                Some(Value::Bool(all)) => *all && dep.dependency_type == DependencyType::Direct,
+                Some(Value::Array(names)) => names.iter().any(|n| n.as_str() == Some(&dep.name)),
+                _ => false,
+            };
         }
 
         Ok(deps)
     }
+
+    fn parse_overrides(&self, content: &str) -> Vec<DependencyOverride> {
+        let package_json: Value = match serde_json::from_str(content) {
+            Ok(value) => value,
+            Err(_) => return Vec::new(),
+        };
+
+        let mut overrides = Vec::new();
+
+        // npm: nested objects scope an override to a parent; "." sets the parent itself
+        if let Some(Value::Object(entries)) = package_json.get("overrides") {
+            collect_npm_overrides(entries, &[], &mut overrides);
+        }
+
+        // yarn: "**/lodash", "webpack/**/terser" or "@scope/pkg"
+        if let Some(Value::Object(entries)) = package_json.get("resolutions") {
+            for (selector, version) in entries {
+                if let Some(version) = version.as_str() {
+                    overrides.push(override_entry(
+                        resolution_target(selector),
+                        selector,
+                        version,
+                        "resolutions",
+                    ));
+                }
+            }
+        }
+
+        // pnpm: "foo@<2" or "parent>child"
+        if let Some(Value::Object(entries)) =
+            package_json.get("pnpm").and_then(|p| p.get("overrides"))
+        {
+            for (selector, version) in entries {
+                if let Some(version) = version.as_str() {
+                    let target = selector.rsplit('>').next().unwrap_or(selector);
+                    overrides.push(override_entry(
+                        split_package_version(target).0,
+                        selector,
+                        version,
+                        "pnpm.overrides",
+                    ));
+                }
+            }
+        }
+
+        overrides
+    }
+
+    /// `package-lock.json` (or `npm-shrinkwrap.json`). Only top-level
+    /// packages are read, not copies nested under other packages.
+    fn lockfile(&self, project_dir: &Path) -> Option<Lockfile> {
+        let (path, content) = ["npm-shrinkwrap.json", "package-lock.json"]
+            .iter()
+            .map(|name| project_dir.join(name))
+            .find_map(|path| fs::read_to_string(&path).ok().map(|c| (path, c)))?;
+        let lock: Value = serde_json::from_str(&content).ok()?;
+
+        let version_of = |entry: &Value| entry.get("version")?.as_str().map(String::from);
+
+        // lockfileVersion 2 and 3 list `packages` by install path, version 1
+        // nests `dependencies` by name
+        let versions = match lock.get("packages").and_then(|p| p.as_object()) {
+            Some(packages) => packages
+                .iter()
+                .filter_map(|(path, entry)| {
+                    let name = path.strip_prefix("node_modules/")?;
+                    if name.contains("/node_modules/") {
+                        return None;
+                    }
+                    Some((name.to_string(), version_of(entry)?))
+                })
+                .collect(),
+            None => lock
+                .get("dependencies")
+                .and_then(|d| d.as_object())
+                .map(|deps| {
+                    deps.iter()
+                        .filter_map(|(name, entry)| Some((name.clone(), version_of(entry)?)))
+                        .collect()
+                })
+                .unwrap_or_default(),
+        };
+
+        Some(Lockfile { path, versions })
+    }
+
+    fn workspace_members(&self, root_dir: &Path) -> Vec<WorkspaceMember> {
+        workspaces::discover(root_dir)
+    }
+
+    fn typed_package(
+        &self,
+        dependency: &Dependency,
+        declared: &[Dependency],
+    ) -> Option<TypedPackage> {
+        let runtime = types::runtime_package(&dependency.name)?;
+        // Node builtins are always available; an alias like
+        // `"lodash-es": "npm:lodash@^4"` declares the package it points at
+        let is_declared = runtime == types::NODE_BUILTINS
+            || declared
+                .iter()
+                .any(|d| d.name == runtime || d.alias_for.as_deref() == Some(runtime.as_str()));
+        Some(TypedPackage {
+            name: runtime,
+            is_declared,
+        })
+    }
+}
+
+/// Split `pkg@range` or `@scope/pkg@range` into name and optional range
+fn split_package_version(spec: &str) -> (&str, Option<&str>) {
+    // Skip the leading `@` of a scoped name when looking for the separator
+    let search_from = usize::from(spec.starts_with('@'));
+    match spec[search_from..].find('@') {
+        Some(pos) => {
+            let pos = pos + search_from;
+            (&spec[..pos], Some(&spec[pos + 1..]))
+        }
+        None => (spec, None),
+    }
+}
+
+/// The package a yarn resolution applies to: the last name in the path
+fn resolution_target(selector: &str) -> &str {
+    let segments: Vec<&str> = selector.split('/').collect();
+    let last = segments.len() - 1;
+    let target = if last > 0 && segments[last - 1].starts_with('@') {
+        let start = selector.len() - segments[last].len() - segments[last - 1].len() - 1;
+        &selector[start..]
+    } else {
+        segments[last]
+    };
+    split_package_version(target).0
+}
+
+fn collect_npm_overrides(
+    entries: &serde_json::Map<String, Value>,
+    parents: &[&str],
+    overrides: &mut Vec<DependencyOverride>,
+) {
+    for (key, value) in entries {
+        match value {
+            Value::String(version) if key == "." => {
+                if let Some(parent) = parents.last() {
+                    overrides.push(override_entry(
+                        split_package_version(parent).0,
+                        &parents.join(" > "),
+                        version,
+                        "overrides",
+                    ));
+                }
+            }
+            Value::String(version) => {
+                let mut path = parents.to_vec();
+                path.push(key);
+                overrides.push(override_entry(
+                    split_package_version(key).0,
+                    &path.join(" > "),
+                    version,
+                    "overrides",
+                ));
+            }
+            Value::Object(nested) => {
+                let mut path = parents.to_vec();
+                path.push(key);
+                collect_npm_overrides(nested, &path, overrides);
+            }
+            _ => {}
+        }
+    }
+}
+
+fn override_entry(
+    package: &str,
+    selector: &str,
+    version: &str,
+    source: &str,
+) -> DependencyOverride {
+    DependencyOverride {
+        package: package.to_string(),
+        selector: selector.to_string(),
+        version: version.to_string(),
+        source: source.to_string(),
+        git_info: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(package_json: &str) -> Vec<Dependency> {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("package.json");
+        fs::write(&path, package_json).unwrap();
+        NodeParser.parse_dependencies(&path).unwrap()
+    }
+
+    #[test]
+    fn npm_alias_specifiers() {
+        let deps = parse(
+            r#"{"dependencies": {
+                "lodash-es": "npm:lodash@^4.17.0",
+                "core": "npm:@scope/core@2",
+                "latest": "npm:left-pad"
+            }}"#,
+        );
+        let alias = |name: &str| {
+            let dep = deps.iter().find(|d| d.name == name).unwrap();
+            (dep.alias_for.as_deref(), dep.version.as_str())
+        };
+        assert_eq!(alias("lodash-es"), (Some("lodash"), "^4.17.0"));
+        assert_eq!(alias("core"), (Some("@scope/core"), "2"));
+        assert_eq!(alias("latest"), (Some("left-pad"), "*"));
+    }
+
+    #[test]
+    fn package_version_split() {
+        assert_eq!(split_package_version("react@^18"), ("react", Some("^18")));
+        assert_eq!(
+            split_package_version("@babel/core@7"),
+            ("@babel/core", Some("7"))
+        );
+        assert_eq!(split_package_version("@babel/core"), ("@babel/core", None));
+    }
+
+    #[test]
+    fn yarn_resolution_targets() {
+        assert_eq!(resolution_target("**/minimist"), "minimist");
+        assert_eq!(resolution_target("webpack/@babel/core"), "@babel/core");
+        assert_eq!(resolution_target("qs@6.5.0"), "qs");
+    }
+
+    #[test]
+    fn typings_of_an_aliased_package_are_not_orphaned() {
+        let deps = parse(
+            r#"{"dependencies": {"lodash-es": "npm:lodash@^4"},
+                "devDependencies": {"@types/lodash": "^4.14.0"}}"#,
+        );
+        let types = deps.iter().find(|d| d.name == "@types/lodash").unwrap();
+        let typed = NodeParser.typed_package(types, &deps).unwrap();
+        assert_eq!(typed.name, "lodash");
+        assert!(typed.is_declared);
+    }
+
+    #[test]
+    fn typings_without_their_package_are_orphaned() {
+        let deps = parse(r#"{"devDependencies": {"@types/lodash": "^4.14.0"}}"#);
+        let typed = NodeParser.typed_package(&deps[0], &deps).unwrap();
+        assert!(!typed.is_declared);
+    }
 }
@@ -1,9 +1,24 @@
-use crate::core::dependency::EcosystemType;
+use crate::core::dependency::{Dependency, EcosystemType, ImportLocation, UsageKind};
 use crate::core::error::Result;
-use crate::ecosystems::traits::ImportScanner;
-use regex::Regex;
+use crate::ecosystems::node::implicit::ImplicitUsageDetector;
+use crate::ecosystems::node::lexer::{self, Token, TokenKind};
+use crate::ecosystems::node::{components, types, workspaces};
+use crate::ecosystems::traits::{ImportScanner, ImportStatement, SourceSegment};
+use std::path::Path;
 
-pub struct NodeScanner;
+pub struct NodeScanner {
+    detectors: Vec<Box<dyn ImplicitUsageDetector>>,
+}
+
+impl NodeScanner {
+    pub fn new(detectors: Vec<Box<dyn ImplicitUsageDetector>>) -> Self {
+        Self { detectors }
+    }
+
+    pub fn add_detector(&mut self, detector: Box<dyn ImplicitUsageDetector>) {
+        self.detectors.push(detector);
+    }
+}
 
 impl ImportScanner for NodeScanner {
     fn ecosystem_type(&self) -> EcosystemType {
@@ -11,40 +26,85 @@ impl ImportScanner for NodeScanner {
     }
 
     fn file_extensions(&self) -> Vec<&'static str> {
-        vec!["js", "ts", "jsx", "tsx", "mjs", "cjs"]
+        vec![
+            "js", "ts", "jsx", "tsx", "mjs", "cjs", "mts", "cts", "vue", "svelte", "astro", "mdx",
+        ]
     }
 
-    fn extract_imports(&self, content: &str) -> Result<Vec<String>> {
-        let mut imports = Vec::new();
+    fn reads_file(&self, file_path: &Path) -> bool {
+        let is_source = file_path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| self.file_extensions().contains(&ext));
+        // Workspace members are found through the root package.json
+        is_source
+            || file_path.ends_with("package.json")
+            || file_path.ends_with("pnpm-workspace.yaml")
+            || self
+                .detectors
+                .iter()
+                .any(|detector| detector.reads_file(file_path))
+    }
+
+    fn version(&self) -> u32 {
+        1
+    }
 
-        // CommonJS require: require('module') or require("module")
-        let require_regex = Regex::new(r#"require\s*\(\s*['"]([^'"]+)['"]\s*\)"#)?;
+    fn source_segments(&self, file_path: &Path, content: &str) -> Vec<SourceSegment> {
+        match file_path.extension().and_then(|ext| ext.to_str()) {
+            Some("vue") | Some("svelte") => components::script_blocks(content),
+            Some("astro") => components::astro_sections(content),
+            Some("mdx") => components::mdx_esm(content),
+            _ => vec![SourceSegment::whole(content)],
+        }
+    }
 
-        // ES6 import: import ... from 'module' or import ... from "module"
-        let import_regex = Regex::new(r#"(?m)^\s*import\s+.*?from\s+['"]([^'"]+)['"]"#)?;
+    fn extract_imports(&self, content: &str) -> Result<Vec<String>> {
+        Ok(find_module_references(content)
+            .into_iter()
+            .map(|import| import.module)
+            .collect())
+    }
 
-        // Dynamic import: import('module') or import("module")
-        let dynamic_import_regex = Regex::new(r#"import\s*\(\s*['"]([^'"]+)['"]\s*\)"#)?;
+    fn find_imports(&self, content: &str) -> Result<Vec<ImportStatement>> {
+        Ok(find_module_references(content))
+    }
 
-        for cap in require_regex.captures_iter(content) {
-            if let Some(module) = cap.get(1) {
-                imports.push(module.as_str().to_string());
-            }
+    fn import_matches(&self, import: &str, dependency_name: &str) -> bool {
+        // Specifiers come straight from the source, so compare package names
+        // exactly rather than by substring ("react" must not match "react-dom")
+        if import.starts_with('.') || import.starts_with('/') {
+            return false;
         }
 
-        for cap in import_regex.captures_iter(content) {
-            if let Some(module) = cap.get(1) {
-                imports.push(module.as_str().to_string());
+        // Typings are used wherever the package they type is imported, or
+        // when pulled in directly by `/// <reference types>`
+        if let Some(runtime) = types::runtime_package(dependency_name) {
+            if types::types_reference_name(dependency_name) == Some(import) {
+                return true;
             }
-        }
-
-        for cap in dynamic_import_regex.captures_iter(content) {
-            if let Some(module) = cap.get(1) {
-                imports.push(module.as_str().to_string());
+            if runtime == types::NODE_BUILTINS {
+                return types::is_builtin(import);
             }
+            return self.normalize_package_name(import) == runtime;
         }
 
-        Ok(imports)
+        self.normalize_package_name(import) == self.normalize_package_name(dependency_name)
+    }
+
+    fn implicit_usages(&self, repo_path: &Path, dependency: &Dependency) -> Vec<ImportLocation> {
+        // Each workspace package has its own scripts and tool configs
+        let mut project_dirs = vec![repo_path.to_path_buf()];
+        project_dirs.extend(workspaces::discover(repo_path).into_iter().map(|m| m.dir));
+
+        project_dirs
+            .iter()
+            .flat_map(|dir| {
+                self.detectors
+                    .iter()
+                    .flat_map(move |detector| detector.detect(dir, dependency))
+            })
+            .collect()
     }
 
     fn extract_package_name(&self, import: &str) -> String {
@@ -75,3 +135,244 @@ impl ImportScanner for NodeScanner {
         pkg_name.trim().to_lowercase()
     }
 }
+
+/// Find every module specifier referenced by a JS/TS source file.
+///
+/// Recognises ESM imports and re-exports (including `import type`), dynamic
+/// `import()`, `require()`, `require.resolve()`, Jest/Vitest module mocks and
+/// triple-slash `/// <reference types>` directives.
+pub(crate) fn find_module_references(content: &str) -> Vec<ImportStatement> {
+    let tokens = lexer::tokenize(content);
+    let mut found = Vec::new();
+
+    for (i, token) in tokens.iter().enumerate() {
+        // Member accesses such as `foo.import(...)` or `obj.require(...)` are not module loads
+        let is_member = i > 0 && tokens[i - 1].kind == TokenKind::Punct('.');
+
+        let reference = match &token.kind {
+            TokenKind::TypeReference(types) => Some((types.clone(), UsageKind::TypeOnly)),
+            TokenKind::Ident(word) if !is_member => match word.as_str() {
+                "import" => import_reference(&tokens[i + 1..]),
+                "export" => export_reference(&tokens[i + 1..]),
+                "require" => require_reference(&tokens[i + 1..]),
+                "jest" | "vi" => mock_reference(&tokens[i + 1..]),
+                _ => None,
+            },
+            _ => None,
+        };
+
+        if let Some((module, kind)) = reference {
+            found.push(ImportStatement {
+                module,
+                line: token.line,
+                kind,
+            });
+        }
+    }
+
+    found
+}
+
+/// `import x from 'm'`, `import 'm'`, `import type { T } from 'm'`, `import('m')`
+fn import_reference(rest: &[Token]) -> Option<(String, UsageKind)> {
+    match rest.first().map(|t| &t.kind) {
+        Some(TokenKind::Str(module)) => Some((module.clone(), UsageKind::Import)),
+        Some(TokenKind::Punct('(')) => call_argument(rest).map(|m| (m, UsageKind::Import)),
+        // import.meta, or `import` used as an object key
+        Some(TokenKind::Punct('.')) | Some(TokenKind::Punct(':')) => None,
+        _ => {
+            let (clause, module) = from_clause(rest)?;
+            let kind = if is_type_only_clause(clause) {
+                UsageKind::TypeOnly
+            } else {
+                UsageKind::Import
+            };
+            Some((module, kind))
+        }
+    }
+}
+
+/// `export * from 'm'`, `export { x } from 'm'`, `export type { T } from 'm'`
+fn export_reference(rest: &[Token]) -> Option<(String, UsageKind)> {
+    let reexport = match rest.first().map(|t| &t.kind) {
+        Some(TokenKind::Punct('*')) | Some(TokenKind::Punct('{')) => true,
+        Some(TokenKind::Ident(word)) if word == "type" => matches!(
+            rest.get(1).map(|t| &t.kind),
+            Some(TokenKind::Punct('*')) | Some(TokenKind::Punct('{'))
+        ),
+        _ => false,
+    };
+    if !reexport {
+        return None;
+    }
+
+    let (clause, module) = from_clause(rest)?;
+    let kind = if is_type_only_clause(clause) {
+        UsageKind::TypeOnly
+    } else {
+        UsageKind::Import
+    };
+    Some((module, kind))
+}
+
+/// `require('m')` and `require.resolve('m')`
+fn require_reference(rest: &[Token]) -> Option<(String, UsageKind)> {
+    let call = match rest.first().map(|t| &t.kind) {
+        Some(TokenKind::Punct('(')) => rest,
+        Some(TokenKind::Punct('.')) if ident_is(rest.get(1), "resolve") => &rest[2..],
+        _ => return None,
+    };
+    call_argument(call).map(|m| (m, UsageKind::Import))
+}
+
+/// `jest.mock('m')`, `jest.requireActual('m')`, `vi.mock('m')`, ...
+fn mock_reference(rest: &[Token]) -> Option<(String, UsageKind)> {
+    if rest.first().map(|t| &t.kind) != Some(&TokenKind::Punct('.')) {
+        return None;
+    }
+    let method = match rest.get(1).map(|t| &t.kind) {
+        Some(TokenKind::Ident(method)) => method.as_str(),
+        _ => return None,
+    };
+    if !matches!(
+        method,
+        "mock" | "unmock" | "doMock" | "requireActual" | "requireMock" | "importActual"
+    ) {
+        return None;
+    }
+    call_argument(&rest[2..]).map(|m| (m, UsageKind::Import))
+}
+
+/// Return the string literal in `( 'm'` at the start of the tokens
+fn call_argument(tokens: &[Token]) -> Option<String> {
+    match (
+        tokens.first().map(|t| &t.kind),
+        tokens.get(1).map(|t| &t.kind),
+    ) {
+        (Some(TokenKind::Punct('(')), Some(TokenKind::Str(module))) => Some(module.clone()),
+        _ => None,
+    }
+}
+
+/// Scan an import/export clause up to `from 'm'`, returning the clause tokens
+/// and the module. Gives up at the end of the statement.
+fn from_clause(tokens: &[Token]) -> Option<(&[Token], String)> {
+    for (i, token) in tokens.iter().enumerate() {
+        match &token.kind {
+            TokenKind::Ident(word) if word == "from" => {
+                if let Some(TokenKind::Str(module)) = tokens.get(i + 1).map(|t| &t.kind) {
+                    return Some((&tokens[..i], module.clone()));
+                }
+            }
+            TokenKind::Ident(word) if matches!(word.as_str(), "import" | "export" | "require") => {
+                return None
+            }
+            TokenKind::Punct(';') | TokenKind::Punct('=') | TokenKind::Punct('(') => return None,
+            _ => {}
+        }
+    }
+    None
+}
+
+/// `type X`, `type { X }`, `type * as X`, or `{ type A, type B }`
+fn is_type_only_clause(clause: &[Token]) -> bool {
+    if ident_is(clause.first(), "type") {
+        // `import type from 'm'` and `import type, { x } from 'm'` import a default named "type"
+        return !matches!(
+            clause.get(1).map(|t| &t.kind),
+            None | Some(TokenKind::Punct(','))
+        );
+    }
+
+    match (clause.first(), clause.last()) {
+        (Some(first), Some(last))
+            if first.kind == TokenKind::Punct('{') && last.kind == TokenKind::Punct('}') =>
+        {
+            let inner = &clause[1..clause.len() - 1];
+            let specifiers: Vec<&[Token]> = inner
+                .split(|t| t.kind == TokenKind::Punct(','))
+                .filter(|s| !s.is_empty())
+                .collect();
+            !specifiers.is_empty() && specifiers.iter().all(|s| ident_is(s.first(), "type"))
+        }
+        _ => false,
+    }
+}
+
+fn ident_is(token: Option<&Token>, expected: &str) -> bool {
+    matches!(token.map(|t| &t.kind), Some(TokenKind::Ident(word)) if word == expected)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn references(content: &str) -> Vec<(String, UsageKind)> {
+        find_module_references(content)
+            .into_iter()
+            .map(|import| (import.module, import.kind))
+            .collect()
+    }
+
+    fn modules(content: &str) -> Vec<String> {
+        references(content).into_iter().map(|(m, _)| m).collect()
+    }
+
+    #[test]
+    fn static_imports_and_reexports() {
+        let source = "import a from 'a';\nimport { b } from \"b\";\nimport 'c';\n\
+                      import * as d from 'd';\nexport { e } from 'e';\nexport * from 'f';";
+        assert_eq!(modules(source), ["a", "b", "c", "d", "e", "f"]);
+    }
+
+    #[test]
+    fn dynamic_import() {
+        assert_eq!(
+            modules("const m = await import('lodash');\nimport(`./${name}`);"),
+            ["lodash"]
+        );
+        // `import.meta` and `import` as a key are not imports
+        assert!(modules("import.meta.url; const o = { import: 'x' };").is_empty());
+    }
+
+    #[test]
+    fn require_and_require_resolve() {
+        assert_eq!(
+            modules("const fs = require('fs');\nrequire.resolve('pkg/package.json');"),
+            ["fs", "pkg/package.json"]
+        );
+        // Member calls and computed specifiers are not module loads
+        assert!(modules("obj.require('x'); require(name);").is_empty());
+    }
+
+    #[test]
+    fn type_only_imports() {
+        let found = references(
+            "import type { A } from 'a';\nimport { type B, type C } from 'b';\n\
+             import { type D, e } from 'c';\nimport type from 'd';",
+        );
+        assert_eq!(
+            found,
+            [
+                ("a".to_string(), UsageKind::TypeOnly),
+                ("b".to_string(), UsageKind::TypeOnly),
+                ("c".to_string(), UsageKind::Import),
+                ("d".to_string(), UsageKind::Import),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_mocks() {
+        assert_eq!(
+            modules("jest.mock('axios');\nvi.importActual('./local');\njest.fn();"),
+            ["axios", "./local"]
+        );
+    }
+
+    #[test]
+    fn lines_are_zero_based() {
+        let found = find_module_references("// header\n\nimport 'a';");
+        assert_eq!(found[0].line, 2);
+    }
+}
@@ -0,0 +1,77 @@
+//! Mapping between DefinitelyTyped `@types/*` packages and the runtime
+//! packages they provide typings for.
+
+/// Pseudo package name used for `@types/node`, which types the Node builtins
+pub const NODE_BUILTINS: &str = "node";
+
+const BUILTIN_MODULES: &[&str] = &[
+    "assert",
+    "async_hooks",
+    "buffer",
+    "child_process",
+    "cluster",
+    "console",
+    "constants",
+    "crypto",
+    "dgram",
+    "diagnostics_channel",
+    "dns",
+    "domain",
+    "events",
+    "fs",
+    "http",
+    "http2",
+    "https",
+    "inspector",
+    "module",
+    "net",
+    "os",
+    "path",
+    "perf_hooks",
+    "process",
+    "punycode",
+    "querystring",
+    "readline",
+    "repl",
+    "stream",
+    "string_decoder",
+    "sys",
+    "timers",
+    "tls",
+    "trace_events",
+    "tty",
+    "url",
+    "util",
+    "v8",
+    "vm",
+    "wasi",
+    "worker_threads",
+    "zlib",
+];
+
+/// Return the name the typing package is referred to by in `types` lists and
+/// `/// <reference types>` directives: `@types/babel__core` -> `babel__core`
+pub fn types_reference_name(dependency_name: &str) -> Option<&str> {
+    dependency_name.strip_prefix("@types/")
+}
+
+/// Return the runtime package typed by an `@types/*` package.
+///
+/// `@types/lodash` -> `lodash`, `@types/babel__core` -> `@babel/core` and
+/// `@types/node` -> [`NODE_BUILTINS`].
+pub fn runtime_package(dependency_name: &str) -> Option<String> {
+    let typed = types_reference_name(dependency_name)?;
+    match typed.split_once("__") {
+        Some((scope, name)) => Some(format!("@{}/{}", scope, name)),
+        None => Some(typed.to_string()),
+    }
+}
+
+/// Check if a module specifier refers to a Node builtin (`fs`, `node:fs`, `fs/promises`)
+pub fn is_builtin(module: &str) -> bool {
+    if module.starts_with("node:") {
+        return true;
+    }
+    let base = module.split('/').next().unwrap_or(module);
+    BUILTIN_MODULES.contains(&base)
+}
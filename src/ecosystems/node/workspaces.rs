@@ -0,0 +1,198 @@
+//! npm/yarn `workspaces` and `pnpm-workspace.yaml` discovery.
+
+use crate::ecosystems::traits::WorkspaceMember;
+use crate::utils::patterns::glob_to_regex;
+use ignore::WalkBuilder;
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+
+/// Find the workspace packages of the project rooted at `root_dir`.
+///
+/// Patterns come from the root package.json `workspaces` field (either an
+/// array or yarn's `{ "packages": [...] }` form) and from `pnpm-workspace.yaml`.
+/// Patterns starting with `!` exclude directories. Directories that are
+/// hidden, ignored by `.gitignore` or installed packages are not searched,
+/// nor any deeper than the patterns reach.
+pub fn discover(root_dir: &Path) -> Vec<WorkspaceMember> {
+    let patterns = workspace_patterns(root_dir);
+    if patterns.is_empty() {
+        return Vec::new();
+    }
+
+    let (excludes, includes): (Vec<_>, Vec<_>) = patterns.iter().partition(|p| p.starts_with('!'));
+    let max_depth = includes
+        .iter()
+        .map(|p| (!p.contains("**")).then(|| p.trim_matches('/').split('/').count()))
+        .try_fold(0, |deepest, depth| Some(depth?.max(deepest)));
+    let includes: Vec<_> = includes
+        .iter()
+        .filter_map(|p| glob_to_regex(p).ok())
+        .collect();
+    let excludes: Vec<_> = excludes
+        .iter()
+        .filter_map(|p| glob_to_regex(&p[1..]).ok())
+        .collect();
+
+    // A revision's snapshot is not a git repository, but has its .gitignore files
+    let mut members: Vec<WorkspaceMember> = WalkBuilder::new(root_dir)
+        .max_depth(max_depth)
+        .require_git(false)
+        .filter_entry(|e| {
+            e.file_type().is_some_and(|ft| ft.is_dir()) && e.file_name() != "node_modules"
+        })
+        .build()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.depth() > 0)
+        .filter_map(|entry| {
+            let relative = entry.path().strip_prefix(root_dir).ok()?;
+            let relative = relative.to_str()?.replace('\\', "/");
+            if !includes.iter().any(|re| re.is_match(&relative))
+                || excludes.iter().any(|re| re.is_match(&relative))
+            {
+                return None;
+            }
+
+            let manifest = entry.path().join("package.json");
+            let content = fs::read_to_string(&manifest).ok()?;
+            let name = serde_json::from_str::<Value>(&content)
+                .ok()
+                .and_then(|v| v.get("name").and_then(|n| n.as_str()).map(String::from))
+                .unwrap_or_else(|| relative.clone());
+
+            Some(WorkspaceMember {
+                name,
+                dir: entry.path().to_path_buf(),
+                manifest,
+            })
+        })
+        .collect();
+
+    members.sort_by(|a, b| a.dir.cmp(&b.dir));
+    members
+}
+
+fn workspace_patterns(root_dir: &Path) -> Vec<String> {
+    let mut patterns = Vec::new();
+
+    if let Some(package_json) = fs::read_to_string(root_dir.join("package.json"))
+        .ok()
+        .and_then(|content| serde_json::from_str::<Value>(&content).ok())
+    {
+        let workspaces = package_json.get("workspaces");
+        let list = workspaces.and_then(|w| w.as_array()).or_else(|| {
+            workspaces
+                .and_then(|w| w.get("packages"))
+                .and_then(|p| p.as_array())
+        });
+        if let Some(list) = list {
+            patterns.extend(list.iter().filter_map(|p| p.as_str()).map(String::from));
+        }
+    }
+
+    if let Ok(content) = fs::read_to_string(root_dir.join("pnpm-workspace.yaml")) {
+        patterns.extend(pnpm_patterns(&content));
+    }
+
+    patterns
+}
+
+/// Read the `packages:` list of a pnpm-workspace.yaml
+fn pnpm_patterns(content: &str) -> Vec<String> {
+    let mut patterns = Vec::new();
+    let mut in_packages = false;
+
+    for line in content.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        if !line.starts_with(' ') && !line.starts_with('-') {
+            in_packages = trimmed == "packages:";
+            continue;
+        }
+        if in_packages {
+            if let Some(item) = trimmed.strip_prefix('-') {
+                let item = item.split(" #").next().unwrap_or(item).trim();
+                patterns.push(item.trim_matches(|c| c == '"' || c == '\'').to_string());
+            }
+        }
+    }
+
+    patterns
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn project(files: &[(&str, &str)]) -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        for (path, content) in files {
+            let path = dir.path().join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        }
+        dir
+    }
+
+    fn names(root: &Path) -> Vec<String> {
+        discover(root).into_iter().map(|m| m.name).collect()
+    }
+
+    #[test]
+    fn packages_matching_a_pattern() {
+        let dir = project(&[
+            ("package.json", r#"{ "workspaces": ["packages/*"] }"#),
+            ("packages/app/package.json", r#"{ "name": "@acme/app" }"#),
+            ("packages/unnamed/package.json", "{}"),
+            ("packages/docs/README.md", ""),
+            (
+                "packages/app/fixtures/package.json",
+                r#"{ "name": "fixture" }"#,
+            ),
+            (
+                "packages/node_modules/dep/package.json",
+                r#"{ "name": "dep" }"#,
+            ),
+            ("packages/build/package.json", r#"{ "name": "built" }"#),
+            (".gitignore", "build/\n"),
+        ]);
+
+        assert_eq!(names(dir.path()), ["@acme/app", "packages/unnamed"]);
+    }
+
+    #[test]
+    fn negated_patterns_exclude_packages() {
+        let dir = project(&[
+            (
+                "package.json",
+                r#"{ "workspaces": { "packages": ["packages/**", "!packages/internal"] } }"#,
+            ),
+            ("packages/ui/package.json", r#"{ "name": "ui" }"#),
+            ("packages/tools/cli/package.json", r#"{ "name": "cli" }"#),
+            (
+                "packages/internal/package.json",
+                r#"{ "name": "internal" }"#,
+            ),
+        ]);
+
+        assert_eq!(names(dir.path()), ["cli", "ui"]);
+    }
+
+    #[test]
+    fn pnpm_workspace_packages() {
+        let dir = project(&[
+            ("package.json", r#"{ "name": "root" }"#),
+            (
+                "pnpm-workspace.yaml",
+                "packages:\n  # Applications\n  - 'apps/*'\n  - \"libs/*\" # shared\n  - '!libs/legacy'\ncatalog:\n  react: ^18\n",
+            ),
+            ("apps/web/package.json", r#"{ "name": "web" }"#),
+            ("libs/core/package.json", r#"{ "name": "core" }"#),
+            ("libs/legacy/package.json", r#"{ "name": "legacy" }"#),
+        ]);
+
+        assert_eq!(names(dir.path()), ["web", "core"]);
+    }
+}
@@ -1,3 +1,4 @@
+mod notebook;
 mod parser;
 mod scanner;
 
@@ -0,0 +1,71 @@
+use crate::ecosystems::traits::SourceSegment;
+use serde_json::Value;
+
+/// Extract the code cells of a Jupyter notebook (`.ipynb`).
+///
+/// Each code cell becomes its own segment so that matches can be reported
+/// by cell index and line within the cell. The index is 0-based and counts
+/// markdown and raw cells too, like positions in nbformat's `cells` array;
+/// only code cells are scanned.
+/// Returns no segments if the notebook is not valid JSON.
+pub fn code_cells(content: &str) -> Vec<SourceSegment> {
+    let notebook: Value = match serde_json::from_str(content) {
+        Ok(value) => value,
+        Err(_) => return Vec::new(),
+    };
+
+    let cells = match notebook.get("cells").and_then(|c| c.as_array()) {
+        Some(cells) => cells,
+        None => return Vec::new(),
+    };
+
+    cells
+        .iter()
+        .enumerate()
+        .filter(|(_, cell)| cell.get("cell_type").and_then(|t| t.as_str()) == Some("code"))
+        .map(|(index, cell)| SourceSegment {
+            content: cell_source(cell),
+            line_offset: 0,
+            cell_index: Some(index),
+        })
+        .collect()
+}
+
+/// nbformat stores cell source either as a single string or as a list of lines
+fn cell_source(cell: &Value) -> String {
+    match cell.get("source") {
+        Some(Value::String(s)) => s.clone(),
+        Some(Value::Array(lines)) => lines.iter().filter_map(|l| l.as_str()).collect(),
+        _ => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn numbers_cells_from_one_counting_markdown() {
+        let notebook = r##"{
+            "cells": [
+                {"cell_type": "markdown", "source": ["# Title\n"]},
+                {"cell_type": "code", "source": ["import numpy as np\n", "np.zeros(3)"]},
+                {"cell_type": "raw", "source": "import not_scanned"},
+                {"cell_type": "code", "source": "import pandas"}
+            ]
+        }"##;
+
+        let cells = code_cells(notebook);
+        assert_eq!(cells.len(), 2);
+        assert_eq!(cells[0].cell_index, Some(1));
+        assert_eq!(cells[0].content, "import numpy as np\nnp.zeros(3)");
+        assert_eq!(cells[1].cell_index, Some(3));
+        assert_eq!(cells[1].content, "import pandas");
+    }
+
+    #[test]
+    fn invalid_notebook_has_no_cells() {
+        assert!(code_cells("not json").is_empty());
+        assert!(code_cells("{}").is_empty());
+    }
+}
@@ -15,12 +15,19 @@ impl DependencyParser for PythonParser {
         vec!["requirements.txt", "pyproject.toml"]
     }
 
+    /// Besides the default names, accepts `requirements-*.txt` files and
+    /// any `.txt` file inside a `requirements/` directory
+    fn can_parse(&self, file_path: &Path) -> bool {
+        let file_name = file_path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+        file_name == "pyproject.toml" || is_requirements_file(file_path)
+    }
+
     fn parse_dependencies(&self, file_path: &Path) -> Result<Vec<Dependency>> {
         let file_name = file_path.file_name().and_then(|n| n.to_str()).unwrap_or("");
 
         match file_name {
-            "requirements.txt" => self.parse_requirements_txt(file_path),
             "pyproject.toml" => self.parse_pyproject_toml(file_path),
+            _ if is_requirements_file(file_path) => self.parse_requirements_txt(file_path),
             _ => Err(DependencyBlameError::ParseError {
                 file: file_path.display().to_string(),
                 reason: "Unsupported file type".to_string(),
@@ -29,6 +36,21 @@ impl DependencyParser for PythonParser {
     }
 }
 
+/// Check if a file follows one of the common requirements file layouts:
+/// `requirements.txt`, `requirements-dev.txt`, `requirements/prod.txt`
+fn is_requirements_file(file_path: &Path) -> bool {
+    let file_name = file_path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+    if !file_name.ends_with(".txt") {
+        return false;
+    }
+    let in_requirements_dir = file_path
+        .parent()
+        .and_then(|p| p.file_name())
+        .map(|n| n == "requirements")
+        .unwrap_or(false);
+    file_name.starts_with("requirements") || in_requirements_dir
+}
+
 impl PythonParser {
     fn parse_requirements_txt(&self, file_path: &Path) -> Result<Vec<Dependency>> {
         let content =
@@ -50,12 +72,12 @@ impl PythonParser {
             // Parse dependency line (handle ==, >=, <=, ~=, etc.)
             let (name, version) = parse_requirement_line(line);
 
-            deps.push(Dependency {
+            deps.push(Dependency::new(
                 name,
                 version,
-                ecosystem: EcosystemType::Python,
-                dependency_type: DependencyType::Direct,
-            });
+                EcosystemType::Python,
+                DependencyType::Direct,
+            ));
         }
 
         Ok(deps)
@@ -98,12 +120,12 @@ impl PythonParser {
                     _ => "*".to_string(),
                 };
 
-                deps.push(Dependency {
-                    name: name.clone(),
+                deps.push(Dependency::new(
+                    name.clone(),
                     version,
-                    ecosystem: EcosystemType::Python,
-                    dependency_type: DependencyType::Direct,
-                });
+                    EcosystemType::Python,
+                    DependencyType::Direct,
+                ));
             }
         }
 
@@ -120,12 +142,12 @@ impl PythonParser {
                     _ => "*".to_string(),
                 };
 
-                deps.push(Dependency {
-                    name: name.clone(),
+                deps.push(Dependency::new(
+                    name.clone(),
                     version,
-                    ecosystem: EcosystemType::Python,
-                    dependency_type: DependencyType::Dev,
-                });
+                    EcosystemType::Python,
+                    DependencyType::Dev,
+                ));
             }
         }
 
@@ -138,12 +160,12 @@ impl PythonParser {
             for dep_str in project_deps {
                 if let Some(dep) = dep_str.as_str() {
                     let (name, version) = parse_requirement_line(dep);
-                    deps.push(Dependency {
+                    deps.push(Dependency::new(
                         name,
                         version,
-                        ecosystem: EcosystemType::Python,
-                        dependency_type: DependencyType::Direct,
-                    });
+                        EcosystemType::Python,
+                        DependencyType::Direct,
+                    ));
                 }
             }
         }
@@ -171,3 +193,25 @@ fn parse_requirement_line(line: &str) -> (String, String) {
     // No version specifier
     (line.trim().to_string(), "*".to_string())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn requirements_file_layouts() {
+        assert!(is_requirements_file(Path::new("requirements.txt")));
+        assert!(is_requirements_file(Path::new("requirements-dev.txt")));
+        assert!(is_requirements_file(Path::new("requirements/prod.txt")));
+        assert!(!is_requirements_file(Path::new("docs/notes.txt")));
+        assert!(!is_requirements_file(Path::new("requirements/README.md")));
+    }
+
+    #[test]
+    fn other_files_are_not_parsed_as_requirements() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("setup.cfg");
+        fs::write(&path, "[metadata]\nname = demo\n").unwrap();
+        assert!(PythonParser.parse_dependencies(&path).is_err());
+    }
+}
@@ -1,7 +1,10 @@
 use crate::core::dependency::EcosystemType;
 use crate::core::error::Result;
-use crate::ecosystems::traits::ImportScanner;
+use crate::ecosystems::python::notebook;
+use crate::ecosystems::traits::{ImportScanner, SourceSegment};
 use regex::Regex;
+use std::path::Path;
+use std::sync::OnceLock;
 
 pub struct PythonScanner;
 
@@ -11,17 +14,43 @@ impl ImportScanner for PythonScanner {
     }
 
     fn file_extensions(&self) -> Vec<&'static str> {
-        vec!["py", "pyw"]
+        vec!["py", "pyw", "pyx", "pxd", "ipynb"]
+    }
+
+    fn version(&self) -> u32 {
+        1
+    }
+
+    fn source_segments(&self, file_path: &Path, content: &str) -> Vec<SourceSegment> {
+        match file_path.extension().and_then(|ext| ext.to_str()) {
+            Some("ipynb") => notebook::code_cells(content),
+            _ => vec![SourceSegment::whole(content)],
+        }
     }
 
     fn extract_imports(&self, content: &str) -> Result<Vec<String>> {
         let mut imports = Vec::new();
 
         // import module
-        let import_regex = Regex::new(r"(?m)^\s*import\s+([a-zA-Z0-9_]+)")?;
+        static IMPORT: OnceLock<Regex> = OnceLock::new();
+        let import_regex = IMPORT
+            .get_or_init(|| Regex::new(r"(?m)^\s*import\s+([a-zA-Z0-9_]+)").expect("valid regex"));
 
         // from module import ...
-        let from_import_regex = Regex::new(r"(?m)^\s*from\s+([a-zA-Z0-9_]+)\s+import")?;
+        static FROM_IMPORT: OnceLock<Regex> = OnceLock::new();
+        let from_import_regex = FROM_IMPORT.get_or_init(|| {
+            Regex::new(r"(?m)^\s*from\s+([a-zA-Z0-9_]+)\s+import").expect("valid regex")
+        });
+
+        // Cython: cimport module / from module.sub cimport ...
+        static CIMPORT: OnceLock<Regex> = OnceLock::new();
+        let cimport_regex = CIMPORT
+            .get_or_init(|| Regex::new(r"(?m)^\s*cimport\s+([a-zA-Z0-9_]+)").expect("valid regex"));
+        static FROM_CIMPORT: OnceLock<Regex> = OnceLock::new();
+        let from_cimport_regex = FROM_CIMPORT.get_or_init(|| {
+            Regex::new(r"(?m)^\s*from\s+([a-zA-Z0-9_]+)[a-zA-Z0-9_.]*\s+cimport")
+                .expect("valid regex")
+        });
 
         for cap in import_regex.captures_iter(content) {
             if let Some(module) = cap.get(1) {
@@ -35,6 +64,18 @@ impl ImportScanner for PythonScanner {
             }
         }
 
+        for cap in cimport_regex.captures_iter(content) {
+            if let Some(module) = cap.get(1) {
+                imports.push(module.as_str().to_string());
+            }
+        }
+
+        for cap in from_cimport_regex.captures_iter(content) {
+            if let Some(module) = cap.get(1) {
+                imports.push(module.as_str().to_string());
+            }
+        }
+
         Ok(imports)
     }
 
@@ -1,8 +1,24 @@
 use super::traits::EcosystemAdapter;
+use crate::core::cache;
 use crate::core::dependency::EcosystemType;
 use crate::core::error::{DependencyBlameError, Result};
+use ignore::WalkBuilder;
 use std::collections::HashMap;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+
+/// A dependency manifest found in a repository, tied to its directory
+#[derive(Debug, Clone)]
+pub struct DetectedManifest {
+    pub ecosystem: EcosystemType,
+    pub path: PathBuf,
+}
+
+impl DetectedManifest {
+    /// Directory containing the manifest; its project root
+    pub fn dir(&self) -> &Path {
+        self.path.parent().unwrap_or(Path::new("."))
+    }
+}
 
 pub struct EcosystemRegistry {
     adapters: HashMap<EcosystemType, Box<dyn EcosystemAdapter>>,
@@ -36,31 +52,163 @@ impl EcosystemRegistry {
         None
     }
 
-    /// Detect ecosystem from a directory by looking for common dependency files
+    /// Whether parsing the manifests of any ecosystem may read this file
+    pub fn parses_file(&self, path: &Path) -> bool {
+        self.adapters
+            .values()
+            .any(|adapter| adapter.parser().reads_file(path))
+    }
+
+    /// Whether parsing manifests or scanning for usages in any ecosystem
+    /// may read this file
+    pub fn reads_file(&self, path: &Path) -> bool {
+        self.parses_file(path)
+            || self
+                .adapters
+                .values()
+                .any(|adapter| adapter.scanner().reads_file(path))
+    }
+
+    /// Detect ecosystem from a directory by looking for common dependency files.
+    /// When several are present, the first in priority order wins.
     pub fn detect_from_directory(&self, dir_path: &Path) -> Result<EcosystemType> {
+        self.ecosystems_in_directory(dir_path)
+            .into_iter()
+            .next()
+            .ok_or_else(|| DependencyBlameError::EcosystemDetectionFailed(dir_path.to_path_buf()))
+    }
+
+    /// Every ecosystem with a dependency file directly in `dir_path`, in priority order
+    pub fn ecosystems_in_directory(&self, dir_path: &Path) -> Vec<EcosystemType> {
+        let mut found = Vec::new();
+
         // Check for Cargo.toml
         if dir_path.join("Cargo.toml").exists() {
-            return Ok(EcosystemType::Rust);
+            found.push(EcosystemType::Rust);
+        }
+
+        // Check for deno.json before package.json, which Deno projects may also have
+        if dir_path.join("deno.json").exists() || dir_path.join("deno.jsonc").exists() {
+            found.push(EcosystemType::Deno);
         }
 
         // Check for package.json
         if dir_path.join("package.json").exists() {
-            return Ok(EcosystemType::Node);
+            found.push(EcosystemType::Node);
         }
 
         // Check for Python files
         if dir_path.join("requirements.txt").exists() || dir_path.join("pyproject.toml").exists() {
-            return Ok(EcosystemType::Python);
+            found.push(EcosystemType::Python);
         }
 
         // Check for go.mod
         if dir_path.join("go.mod").exists() {
-            return Ok(EcosystemType::Go);
+            found.push(EcosystemType::Go);
+        }
+
+        found.retain(|ecosystem| self.adapters.contains_key(ecosystem));
+        found
+    }
+
+    /// Find every dependency manifest in the repository, at the root and in
+    /// subdirectories. Respects .gitignore and skips installed package
+    /// directories. Root manifests come first, then by path.
+    pub fn detect_manifests(&self, repo_path: &Path) -> Result<Vec<DetectedManifest>> {
+        let mut manifests = Vec::new();
+
+        let outside_cache = cache::skip_cache_dir(repo_path);
+        // A revision's snapshot is not a git repository, but has its .gitignore files
+        let dirs = WalkBuilder::new(repo_path)
+            .require_git(false)
+            .filter_entry(move |entry| {
+                let name = entry.file_name().to_str().unwrap_or("");
+                !is_package_dir(name) && outside_cache(entry)
+            })
+            .build()
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_type().map(|ft| ft.is_dir()).unwrap_or(false));
+
+        for dir in dirs {
+            for ecosystem in self.ecosystems_in_directory(dir.path()) {
+                manifests.push(DetectedManifest {
+                    ecosystem,
+                    path: self.get_dependency_file(dir.path(), ecosystem)?,
+                });
+            }
         }
 
-        Err(DependencyBlameError::EcosystemDetectionFailed(
-            dir_path.to_path_buf(),
-        ))
+        if manifests.is_empty() {
+            return Err(DependencyBlameError::EcosystemDetectionFailed(
+                repo_path.to_path_buf(),
+            ));
+        }
+
+        manifests.sort_by(|a, b| {
+            let depth = |m: &DetectedManifest| m.path.components().count();
+            depth(a).cmp(&depth(b)).then_with(|| a.path.cmp(&b.path))
+        });
+        Ok(manifests)
+    }
+
+    /// The ecosystem of a file that [`detect_manifests`](Self::detect_manifests)
+    /// would report, judging by its path alone, so that it also works for
+    /// files that only exist in the history
+    pub fn manifest_ecosystem(&self, path: &Path) -> Option<EcosystemType> {
+        let in_package_dir = path
+            .parent()
+            .into_iter()
+            .flat_map(Path::components)
+            .any(|component| component.as_os_str().to_str().is_some_and(is_package_dir));
+        if in_package_dir {
+            return None;
+        }
+        let file_name = path.file_name()?.to_str()?;
+        self.adapters
+            .values()
+            .find(|adapter| adapter.parser().supported_files().contains(&file_name))
+            .map(|adapter| adapter.ecosystem_type())
+    }
+
+    /// Pick the manifests to analyze.
+    ///
+    /// An explicit `manifest` (relative to `repo_path`) is used as-is, parsed
+    /// as `ecosystem` when given and otherwise as whichever ecosystem's parser
+    /// accepts it. Without one, every detected manifest is used, optionally
+    /// narrowed down to `ecosystem`.
+    pub fn select_manifests(
+        &self,
+        repo_path: &Path,
+        manifest: Option<&Path>,
+        ecosystem: Option<EcosystemType>,
+    ) -> Result<Vec<DetectedManifest>> {
+        if let Some(manifest) = manifest {
+            let path = repo_path.join(manifest);
+            if !path.is_file() {
+                return Err(DependencyBlameError::DependencyFileNotFound(
+                    path.display().to_string(),
+                ));
+            }
+            let ecosystem = match ecosystem.or_else(|| self.detect_ecosystem(&path)) {
+                Some(ecosystem) if self.adapters.contains_key(&ecosystem) => ecosystem,
+                Some(_) => return Err(DependencyBlameError::UnsupportedEcosystem),
+                None => return Err(DependencyBlameError::EcosystemDetectionFailed(path)),
+            };
+            return Ok(vec![DetectedManifest { ecosystem, path }]);
+        }
+
+        let mut manifests = self.detect_manifests(repo_path)?;
+        if let Some(ecosystem) = ecosystem {
+            manifests.retain(|m| m.ecosystem == ecosystem);
+            if manifests.is_empty() {
+                return Err(DependencyBlameError::DependencyFileNotFound(format!(
+                    "no {} manifest in {}",
+                    ecosystem.as_str(),
+                    repo_path.display()
+                )));
+            }
+        }
+        Ok(manifests)
     }
 
     /// Get dependency file path for an ecosystem
@@ -68,7 +216,7 @@ impl EcosystemRegistry {
         &self,
         dir_path: &Path,
         ecosystem: EcosystemType,
-    ) -> Result<std::path::PathBuf> {
+    ) -> Result<PathBuf> {
         let file_name = match ecosystem {
             EcosystemType::Rust => "Cargo.toml",
             EcosystemType::Node => "package.json",
@@ -81,6 +229,13 @@ impl EcosystemRegistry {
                 "requirements.txt"
             }
             EcosystemType::Go => "go.mod",
+            EcosystemType::Deno => {
+                let jsonc = dir_path.join("deno.jsonc");
+                if !dir_path.join("deno.json").exists() && jsonc.exists() {
+                    return Ok(jsonc);
+                }
+                "deno.json"
+            }
         };
 
         let file_path = dir_path.join(file_name);
@@ -107,6 +262,13 @@ pub fn create_default_registry() -> EcosystemRegistry {
     registry.register(Box::new(crate::ecosystems::node::NodeAdapter::new()));
     registry.register(Box::new(crate::ecosystems::python::PythonAdapter::new()));
     registry.register(Box::new(crate::ecosystems::go::GoAdapter::new()));
+    registry.register(Box::new(crate::ecosystems::deno::DenoAdapter::new()));
 
     registry
 }
+
+/// Directories of installed or built packages, whose manifests belong to
+/// other projects
+fn is_package_dir(name: &str) -> bool {
+    matches!(name, "node_modules" | "target" | "vendor" | "__pycache__")
+}
@@ -1,6 +1,6 @@
 use crate::core::dependency::{Dependency, DependencyType, EcosystemType};
 use crate::core::error::{DependencyBlameError, Result};
-use crate::ecosystems::traits::DependencyParser;
+use crate::ecosystems::traits::{DependencyParser, Lockfile};
 use std::fs;
 use std::path::Path;
 
@@ -15,6 +15,10 @@ impl DependencyParser for RustParser {
         vec!["Cargo.toml"]
     }
 
+    fn reads_file(&self, file_path: &Path) -> bool {
+        self.can_parse(file_path) || file_path.file_name().is_some_and(|n| n == "Cargo.lock")
+    }
+
     fn parse_dependencies(&self, file_path: &Path) -> Result<Vec<Dependency>> {
         let content =
             fs::read_to_string(file_path).map_err(|e| DependencyBlameError::ParseError {
@@ -34,12 +38,12 @@ impl DependencyParser for RustParser {
         if let Some(dependencies) = cargo_toml.get("dependencies").and_then(|v| v.as_table()) {
             for (name, value) in dependencies {
                 let version = extract_version(value);
-                deps.push(Dependency {
-                    name: name.clone(),
+                deps.push(Dependency::new(
+                    name.clone(),
                     version,
-                    ecosystem: EcosystemType::Rust,
-                    dependency_type: DependencyType::Direct,
-                });
+                    EcosystemType::Rust,
+                    DependencyType::Direct,
+                ));
             }
         }
 
@@ -50,12 +54,12 @@ impl DependencyParser for RustParser {
         {
             for (name, value) in dev_dependencies {
                 let version = extract_version(value);
-                deps.push(Dependency {
-                    name: name.clone(),
+                deps.push(Dependency::new(
+                    name.clone(),
                     version,
-                    ecosystem: EcosystemType::Rust,
-                    dependency_type: DependencyType::Dev,
-                });
+                    EcosystemType::Rust,
+                    DependencyType::Dev,
+                ));
             }
         }
 
@@ -66,17 +70,38 @@ impl DependencyParser for RustParser {
         {
             for (name, value) in build_dependencies {
                 let version = extract_version(value);
-                deps.push(Dependency {
-                    name: name.clone(),
+                deps.push(Dependency::new(
+                    name.clone(),
                     version,
-                    ecosystem: EcosystemType::Rust,
-                    dependency_type: DependencyType::Build,
-                });
+                    EcosystemType::Rust,
+                    DependencyType::Build,
+                ));
             }
         }
 
         Ok(deps)
     }
+
+    /// `Cargo.lock`. When several versions of a package are locked, the
+    /// highest is reported.
+    fn lockfile(&self, project_dir: &Path) -> Option<Lockfile> {
+        let path = project_dir.join("Cargo.lock");
+        let lock: toml::Value = toml::from_str(&fs::read_to_string(&path).ok()?).ok()?;
+
+        // Packages are sorted by name and version, so later entries win
+        let versions = lock
+            .get("package")?
+            .as_array()?
+            .iter()
+            .filter_map(|package| {
+                let name = package.get("name")?.as_str()?;
+                let version = package.get("version")?.as_str()?;
+                Some((name.to_string(), version.to_string()))
+            })
+            .collect();
+
+        Some(Lockfile { path, versions })
+    }
 }
 
 fn extract_version(value: &toml::Value) -> String {
@@ -2,6 +2,7 @@ use crate::core::dependency::EcosystemType;
 use crate::core::error::Result;
 use crate::ecosystems::traits::ImportScanner;
 use regex::Regex;
+use std::sync::OnceLock;
 
 pub struct RustScanner;
 
@@ -14,14 +15,23 @@ impl ImportScanner for RustScanner {
         vec!["rs"]
     }
 
+    fn version(&self) -> u32 {
+        1
+    }
+
     fn extract_imports(&self, content: &str) -> Result<Vec<String>> {
         let mut imports = Vec::new();
 
         // Regex for "use" statements: use foo; use foo::bar; use foo::{bar, baz};
-        let use_regex = Regex::new(r"(?m)^\s*use\s+([a-zA-Z0-9_]+)")?;
+        static USE: OnceLock<Regex> = OnceLock::new();
+        let use_regex =
+            USE.get_or_init(|| Regex::new(r"(?m)^\s*use\s+([a-zA-Z0-9_]+)").expect("valid regex"));
 
         // Regex for "extern crate" statements
-        let extern_regex = Regex::new(r"(?m)^\s*extern\s+crate\s+([a-zA-Z0-9_]+)")?;
+        static EXTERN_CRATE: OnceLock<Regex> = OnceLock::new();
+        let extern_regex = EXTERN_CRATE.get_or_init(|| {
+            Regex::new(r"(?m)^\s*extern\s+crate\s+([a-zA-Z0-9_]+)").expect("valid regex")
+        });
 
         for cap in use_regex.captures_iter(content) {
             if let Some(module) = cap.get(1) {
@@ -1,6 +1,55 @@
-use crate::core::dependency::{Dependency, EcosystemType};
+use crate::core::dependency::{
+    Dependency, DependencyOverride, EcosystemType, ImportLocation, TypedPackage, UsageKind,
+};
 use crate::core::error::Result;
-use std::path::Path;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// A region of a source file that should be scanned for imports
+#[derive(Debug, Clone)]
+pub struct SourceSegment {
+    pub content: String,
+    /// Number of lines in the original file that precede this segment
+    pub line_offset: usize,
+    /// Notebook cell this segment was taken from, if any, 0-based and
+    /// counting every cell of the notebook
+    pub cell_index: Option<usize>,
+}
+
+impl SourceSegment {
+    pub fn whole(content: &str) -> Self {
+        Self {
+            content: content.to_string(),
+            line_offset: 0,
+            cell_index: None,
+        }
+    }
+}
+
+/// A package within a multi-package repository (monorepo workspace)
+#[derive(Debug, Clone)]
+pub struct WorkspaceMember {
+    pub name: String,
+    pub dir: PathBuf,
+    pub manifest: PathBuf,
+}
+
+/// An import found by a scanner, positioned within the scanned content
+#[derive(Debug, Clone)]
+pub struct ImportStatement {
+    pub module: String,
+    /// 0-based line index within the scanned content
+    pub line: usize,
+    pub kind: UsageKind,
+}
+
+/// Versions resolved by a project's lockfile
+#[derive(Debug, Clone)]
+pub struct Lockfile {
+    pub path: PathBuf,
+    /// Locked version of each package, by name
+    pub versions: HashMap<String, String>,
+}
 
 /// Trait for parsing dependency files
 pub trait DependencyParser: Send + Sync {
@@ -19,11 +68,44 @@ pub trait DependencyParser: Send + Sync {
         self.supported_files().contains(&file_name)
     }
 
+    /// Whether parsing a project may read this file: its manifests, and
+    /// lockfiles or workspace definitions next to them. A revision's other
+    /// files are not written out for `--rev`.
+    fn reads_file(&self, file_path: &Path) -> bool {
+        self.can_parse(file_path)
+    }
+
     /// Find a specific dependency by name
     fn find_dependency(&self, file_path: &Path, dep_name: &str) -> Result<Option<Dependency>> {
         let deps = self.parse_dependencies(file_path)?;
         Ok(deps.into_iter().find(|d| d.name == dep_name))
     }
+
+    /// Find the workspace packages of a monorepo rooted at `root_dir`.
+    /// Their manifests are parsed in addition to the root one.
+    fn workspace_members(&self, _root_dir: &Path) -> Vec<WorkspaceMember> {
+        Vec::new()
+    }
+
+    /// Parse version overrides from the content of a dependency file. Takes
+    /// the content rather than a path so historical revisions can be checked.
+    fn parse_overrides(&self, _content: &str) -> Vec<DependencyOverride> {
+        Vec::new()
+    }
+
+    /// For typings-only packages, return the runtime package they provide
+    /// types for and whether it is still among the declared dependencies
+    fn typed_package(
+        &self,
+        _dependency: &Dependency,
+        _declared: &[Dependency],
+    ) -> Option<TypedPackage> {
+        None
+    }
+    /// Read the lockfile of the project rooted at `project_dir`, if it has one
+    fn lockfile(&self, _project_dir: &Path) -> Option<Lockfile> {
+        None
+    }
 }
 
 /// Trait for scanning codebase for imports/usage
@@ -34,19 +116,71 @@ pub trait ImportScanner: Send + Sync {
     /// Returns file extensions to scan (e.g., ["rs"] for Rust)
     fn file_extensions(&self) -> Vec<&'static str>;
 
+    /// Whether scanning a project may read this file: sources with one of
+    /// the `file_extensions`, and whatever `implicit_usages` looks at
+    fn reads_file(&self, file_path: &Path) -> bool {
+        file_path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| self.file_extensions().contains(&ext))
+    }
+
+    /// Version of the import extraction, part of the key of cached import
+    /// results. Bump it with any change to what the scanner finds in a file.
+    fn version(&self) -> u32;
+
+    /// Split a file into the segments that contain scannable source code.
+    /// Most languages scan the whole file; container formats such as
+    /// notebooks override this.
+    fn source_segments(&self, _file_path: &Path, content: &str) -> Vec<SourceSegment> {
+        vec![SourceSegment::whole(content)]
+    }
+
+    /// Find usages that are not imports in source files, such as references
+    /// in project configuration
+    fn implicit_usages(&self, _repo_path: &Path, _dependency: &Dependency) -> Vec<ImportLocation> {
+        Vec::new()
+    }
+
     /// Extract import statements from file content
     fn extract_imports(&self, content: &str) -> Result<Vec<String>>;
 
+    /// Find all imports in the content together with the line they start on.
+    /// The default implementation runs `extract_imports` line by line.
+    fn find_imports(&self, content: &str) -> Result<Vec<ImportStatement>> {
+        let mut found = Vec::new();
+        for (line, text) in content.lines().enumerate() {
+            for module in self.extract_imports(text)? {
+                found.push(ImportStatement {
+                    module,
+                    line,
+                    kind: UsageKind::Import,
+                });
+            }
+        }
+        Ok(found)
+    }
+
+    /// Check if an extracted import refers to the given dependency
+    fn import_matches(&self, import: &str, dependency_name: &str) -> bool {
+        let normalized_dep = self.normalize_package_name(dependency_name);
+        let normalized_imp = self.normalize_package_name(import);
+        normalized_imp.contains(&normalized_dep) || normalized_dep.contains(&normalized_imp)
+    }
+
+    /// Check if an extracted import refers to the given dependency. Scanners
+    /// that need more than the name (e.g. an aliased real package) override this.
+    fn dependency_matches(&self, import: &str, dependency: &Dependency) -> bool {
+        self.import_matches(import, &dependency.name)
+    }
+
     /// Check if dependency is imported in the given content
     fn is_dependency_imported(&self, content: &str, dependency_name: &str) -> bool {
         self.extract_imports(content)
             .map(|imports| {
-                let normalized_dep = self.normalize_package_name(dependency_name);
-                imports.iter().any(|imp| {
-                    let normalized_imp = self.normalize_package_name(imp);
-                    normalized_imp.contains(&normalized_dep)
-                        || normalized_dep.contains(&normalized_imp)
-                })
+                imports
+                    .iter()
+                    .any(|imp| self.import_matches(imp, dependency_name))
             })
             .unwrap_or(false)
     }
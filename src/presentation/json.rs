@@ -1,5 +1,8 @@
+use crate::core::authors::AuthorStats;
 use crate::core::dependency::{Dependency, DependencyAnalysis};
+use crate::core::diff::DependencyDiff;
 use crate::core::error::Result;
+use crate::core::graveyard::RemovedDependency;
 
 pub fn print_analysis(analysis: &DependencyAnalysis) -> Result<()> {
     let json = serde_json::to_string_pretty(analysis)?;
@@ -12,3 +15,27 @@ pub fn print_dependency_list(dependencies: &[Dependency]) -> Result<()> {
     println!("{}", json);
     Ok(())
 }
+
+pub fn print_audit(analyses: &[DependencyAnalysis]) -> Result<()> {
+    let json = serde_json::to_string_pretty(analyses)?;
+    println!("{}", json);
+    Ok(())
+}
+
+pub fn print_diff(diff: &DependencyDiff) -> Result<()> {
+    let json = serde_json::to_string_pretty(diff)?;
+    println!("{}", json);
+    Ok(())
+}
+
+pub fn print_graveyard(removed: &[RemovedDependency]) -> Result<()> {
+    let json = serde_json::to_string_pretty(removed)?;
+    println!("{}", json);
+    Ok(())
+}
+
+pub fn print_authors(authors: &[AuthorStats]) -> Result<()> {
+    let json = serde_json::to_string_pretty(authors)?;
+    println!("{}", json);
+    Ok(())
+}
@@ -0,0 +1,63 @@
+use crate::core::diff::{ChangeKind, DependencyDiff};
+use crate::presentation::text::change_summary;
+
+/// Print a diff as Markdown, grouped by kind of change, for release notes
+pub fn print_diff(diff: &DependencyDiff) {
+    println!("## Dependency changes: {} → {}\n", diff.from, diff.to);
+
+    if diff.changes.is_empty() {
+        println!("No dependency changes.");
+        return;
+    }
+
+    let kinds = [
+        (ChangeKind::Added, "Added"),
+        (ChangeKind::Removed, "Removed"),
+        (ChangeKind::Upgraded, "Upgraded"),
+        (ChangeKind::Downgraded, "Downgraded"),
+        (ChangeKind::Changed, "Changed"),
+        (ChangeKind::Reclassified, "Reclassified"),
+    ];
+
+    for (kind, heading) in kinds {
+        let changes: Vec<_> = diff.changes.iter().filter(|c| c.kind == kind).collect();
+        if changes.is_empty() {
+            continue;
+        }
+
+        println!("### {}\n", heading);
+        for change in changes {
+            let manifest = change
+                .manifest
+                .as_ref()
+                .map(|m| format!(" (`{}`)", m.display()))
+                .unwrap_or_default();
+            println!(
+                "- **{}**{}: {}",
+                escape(&change.name),
+                manifest,
+                escape(&change_summary(change))
+            );
+            for commit in &change.commits {
+                println!(
+                    "  - `{}` {}",
+                    &commit.commit_hash[..8],
+                    escape(commit.message.lines().next().unwrap_or("").trim())
+                );
+            }
+        }
+        println!();
+    }
+}
+
+/// Escape characters that Markdown would otherwise format
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '*' | '_' | '`' | '[' | ']' | '<' | '#' | '|' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
@@ -1,3 +1,4 @@
 pub mod json;
+pub mod markdown;
 pub mod text;
 pub mod tui;
@@ -1,4 +1,14 @@
-use crate::core::dependency::{Dependency, DependencyAnalysis};
+use crate::core::authors::AuthorStats;
+use crate::core::dependency::{
+    Dependency, DependencyAnalysis, DependencyType, EcosystemType, GitInfo, ShallowHistory,
+    UsageKind,
+};
+use crate::core::diff::{DependencyChange, DependencyDiff, DependencyState};
+use crate::core::graveyard::RemovedDependency;
+use crate::core::hooks;
+use crate::core::merge::PullRequest;
+use crate::core::rationale::{Rationale, RationaleSource};
+use std::path::Path;
 
 pub fn print_analysis(analysis: &DependencyAnalysis) {
     println!("\n{}", "=".repeat(60));
@@ -11,16 +21,134 @@ pub fn print_analysis(analysis: &DependencyAnalysis) {
     // Dependency info
     println!("Type: {}", analysis.dependency.dependency_type.as_str());
     println!("Ecosystem: {}", analysis.dependency.ecosystem.as_str());
+    if let Some(manifest) = &analysis.dependency.manifest {
+        println!("Manifest: {}", manifest.display());
+    }
+    if let Some(workspace) = &analysis.dependency.workspace {
+        println!("Workspace: {}", workspace);
+    }
+    if analysis.dependency.is_internal {
+        println!("Internal: links to another package in this repository");
+    }
+    if let Some(real) = &analysis.dependency.alias_for {
+        println!("Alias for: {}", real);
+    }
+    if analysis.dependency.is_bundled {
+        println!("Bundled: shipped inside the published package");
+    }
+    if let Some(reason) = &analysis.dependency.ignore_reason {
+        println!("Ignored: {}", reason);
+    }
+    if let Some(rationale) = &analysis.rationale {
+        println!(
+            "Rationale: {} ({})",
+            rationale.reason,
+            rationale_source(rationale)
+        );
+    }
+    if let Some(removed) = &analysis.removed {
+        println!(
+            "Status: REMOVED in {} on {} ({} days after it was added)",
+            &removed.removed.commit_hash[..8],
+            removed.removed.date.format("%Y-%m-%d"),
+            removed.lifetime_days
+        );
+        println!("Removal message:\n{}", removed.removed.message.trim());
+    }
+
+    if let Some(typed) = &analysis.typed_package {
+        println!("Provides types for: {}", typed.name);
+        if !typed.is_declared {
+            println!(
+                "WARNING: orphaned typings - '{}' is no longer a dependency",
+                typed.name
+            );
+        }
+    }
+
+    if !analysis.overrides.is_empty() {
+        println!("\n{}", "-".repeat(60));
+        println!("Version Overrides:");
+        println!("{}", "-".repeat(60));
+        for entry in &analysis.overrides {
+            println!("{} \"{}\": {}", entry.source, entry.selector, entry.version);
+            match &entry.git_info {
+                Some(git_info) => println!(
+                    "  Added in {} by {} on {}: {}",
+                    commit_ref(git_info),
+                    git_info.author,
+                    git_info.date.format("%Y-%m-%d"),
+                    git_info.message.lines().next().unwrap_or("").trim()
+                ),
+                None => println!("  Added in: unknown"),
+            }
+        }
+    }
 
     // Git history
     if let Some(git_info) = &analysis.git_info {
         println!("\n{}", "-".repeat(60));
         println!("Git History:");
         println!("{}", "-".repeat(60));
-        println!("Added in: {}", &git_info.commit_hash[..8]);
+        println!("Added in: {}", commit_ref(git_info));
+        // The manifest was renamed since, or the dependency moved in from another one
+        let manifest = analysis.dependency.manifest.as_ref();
+        if manifest.is_some_and(|m| *m != git_info.file_path) {
+            println!("File: {}", git_info.file_path.display());
+        }
         println!("Author: {}", git_info.author);
         println!("Date: {}", git_info.date.format("%Y-%m-%d %H:%M:%S UTC"));
         println!("Message:\n{}", git_info.message.trim());
+        print_message_details(git_info);
+        if let Some(merge) = &git_info.merged_in {
+            println!(
+                "\nMerged in: {} by {} on {}",
+                &merge.commit_hash[..8],
+                merge.author,
+                merge.date.format("%Y-%m-%d")
+            );
+        }
+        if let Some(pull_request) = &git_info.pull_request {
+            println!("Pull request: {}", pull_request_summary(pull_request));
+        }
+        if let Some(blame) = &analysis.blame {
+            let lines = if blame.start_line == blame.end_line {
+                format!("line {}", blame.start_line)
+            } else {
+                format!("lines {}-{}", blame.start_line, blame.end_line)
+            };
+            println!("\nManifest entry ({}):", lines);
+            for (label, commit) in [
+                ("Last changed", &blame.last_changed),
+                ("Line introduced", &blame.introduced),
+            ] {
+                println!(
+                    "  {}: {} by {} on {}: {}",
+                    label,
+                    commit_ref(commit),
+                    commit.author,
+                    commit.date.format("%Y-%m-%d"),
+                    commit.message.lines().next().unwrap_or("").trim()
+                );
+                if manifest.is_some_and(|m| *m != commit.file_path) {
+                    println!("    (in {})", commit.file_path.display());
+                }
+            }
+        }
+        if let Some(shallow) = &analysis.shallow {
+            print_shallow_note(shallow);
+        }
+    } else if let Some(local_change) = &analysis.local_change {
+        println!("\n{}", "-".repeat(60));
+        println!("Git History:");
+        println!("{}", "-".repeat(60));
+        println!(
+            "Added in your working copy ({}, not committed yet)",
+            local_change.state.as_str()
+        );
+        if let Some(author) = &local_change.author {
+            println!("Author: {}", author);
+        }
     } else {
         println!("\n{}", "-".repeat(60));
         println!(
@@ -29,16 +157,61 @@ pub fn print_analysis(analysis: &DependencyAnalysis) {
         println!("{}", "-".repeat(60));
     }
 
+    if let Some(releases) = &analysis.releases {
+        println!("\n{}", "-".repeat(60));
+        println!("Releases:");
+        println!("{}", "-".repeat(60));
+        match (&releases.first, &analysis.removed) {
+            (Some(first), _) => println!(
+                "First shipped in: {} ({})",
+                first.tag,
+                first.date.format("%Y-%m-%d")
+            ),
+            (None, Some(_)) => println!("Never shipped: removed before any release included it"),
+            (None, None) => println!("First shipped in: not released yet"),
+        }
+        if let (Some(_), Some(removed)) = (&releases.first, &analysis.removed) {
+            match &releases.first_without {
+                Some(without) => println!(
+                    "Not shipped since: {} ({})",
+                    without.tag,
+                    without.date.format("%Y-%m-%d")
+                ),
+                None => println!(
+                    "Removed in {}, which is not released yet",
+                    &removed.removed.commit_hash[..8]
+                ),
+            }
+        }
+    }
+
     // Usage analysis
     println!("\n{}", "-".repeat(60));
     println!("Usage Analysis:");
     println!("{}", "-".repeat(60));
 
-    if analysis.usage_info.is_used {
+    if analysis.dependency.dependency_type == DependencyType::Override {
+        println!("Status: OVERRIDE (a transitive package whose version is forced)");
+        println!("\nIt is not declared as a dependency, so imports are not checked.");
+    } else if analysis.usage_info.is_used {
         println!(
             "Status: USED ({} imports found)",
             analysis.usage_info.usage_count
         );
+        if analysis.usage_info.type_only_count > 0 {
+            println!(
+                "Type-only imports: {} (not needed at runtime)",
+                analysis.usage_info.type_only_count
+            );
+        }
+        if analysis.usage_info.is_type_only() {
+            println!("Note: only types are imported; this could be a development dependency");
+        }
+        if analysis.usage_info.is_test_only() {
+            println!("Note: only used in tests; this could be a development dependency");
+        } else if analysis.usage_info.test_count > 0 {
+            println!("Test usages: {}", analysis.usage_info.test_count);
+        }
         println!("\nLocations:");
 
         let max_display = 10;
@@ -49,13 +222,35 @@ pub fn print_analysis(analysis: &DependencyAnalysis) {
             .take(max_display)
             .enumerate()
         {
+            let cell = location
+                .cell_index
+                // Numbered from 1 in the text, as Jupyter shows cells
+                .map(|index| format!(" [cell {}]", index + 1))
+                .unwrap_or_default();
+            let workspace = location
+                .workspace
+                .as_ref()
+                .map(|w| format!(" ({})", w))
+                .unwrap_or_default();
+            let test = if location.is_test { " [test]" } else { "" };
             println!(
-                "  {}. {}:{}",
+                "  {}. {}{}:{}{}{}",
                 i + 1,
                 location.file_path.display(),
-                location.line_number
+                cell,
+                location.line_number,
+                workspace,
+                test
             );
-            println!("     {}", location.line_content);
+            if location.kind == UsageKind::Import {
+                println!("     {}", location.line_content);
+            } else {
+                println!(
+                    "     {} ({})",
+                    location.line_content,
+                    location.kind.as_str()
+                );
+            }
         }
 
         if analysis.usage_info.usage_count > max_display {
@@ -66,10 +261,14 @@ pub fn print_analysis(analysis: &DependencyAnalysis) {
         }
     } else {
         println!("Status: UNUSED (no imports found)");
-        println!("\nThis dependency might be:");
-        println!("  - Unused and safe to remove");
-        println!("  - A transitive dependency");
-        println!("  - Used in a way not detected by import scanning");
+        if let Some(reason) = &analysis.dependency.ignore_reason {
+            println!("\nIgnored by config: {}", reason);
+        } else {
+            println!("\nThis dependency might be:");
+            println!("  - Unused and safe to remove");
+            println!("  - A transitive dependency");
+            println!("  - Used in a way not detected by import scanning");
+        }
     }
 
     println!("\n{}\n", "=".repeat(60));
@@ -80,6 +279,126 @@ pub fn print_dependency_list(dependencies: &[Dependency]) {
     println!("Dependencies ({} total)", dependencies.len());
     println!("{}\n", "=".repeat(60));
 
+    for (ecosystem, manifest, deps) in &manifest_groups(dependencies, |dep| dep) {
+        print_manifest_header(*ecosystem, *manifest);
+        print_dependency_sections(deps);
+    }
+
+    println!("{}\n", "=".repeat(60));
+}
+
+pub fn print_audit(analyses: &[DependencyAnalysis], scanned: bool) {
+    println!("\n{}", "=".repeat(60));
+    println!("Dependency Audit ({} total)", analyses.len());
+    println!("{}\n", "=".repeat(60));
+
+    for (ecosystem, manifest, entries) in &manifest_groups(analyses, |a| &a.dependency) {
+        print_manifest_header(*ecosystem, *manifest);
+        for analysis in entries {
+            let dep = &analysis.dependency;
+            let status = match usage_status(analysis) {
+                Some(status) if scanned => format!(" - {}", status),
+                _ => String::new(),
+            };
+            println!(
+                "  {} ({}){}{}",
+                dep.name,
+                dep.version,
+                annotations(dep),
+                status
+            );
+            if let Some(rationale) = &analysis.rationale {
+                println!(
+                    "    Rationale: {} ({})",
+                    rationale.reason,
+                    rationale_source(rationale)
+                );
+            }
+            if let Some(git_info) = &analysis.git_info {
+                println!(
+                    "    Added: {} {} by {}: {}",
+                    commit_ref(git_info),
+                    git_info.date.format("%Y-%m-%d"),
+                    git_info.author,
+                    git_info.message.lines().next().unwrap_or("").trim()
+                );
+                if let Some(reason) = &git_info.reason {
+                    println!("    Reason: {}", reason);
+                }
+            }
+            if let Some(releases) = &analysis.releases {
+                match &releases.first {
+                    Some(first) => println!(
+                        "    Shipped since: {} ({})",
+                        first.tag,
+                        first.date.format("%Y-%m-%d")
+                    ),
+                    None => println!("    Shipped since: not released yet"),
+                }
+            }
+        }
+        println!();
+    }
+
+    println!("{}\n", "=".repeat(60));
+}
+
+/// Where a rationale was recorded, for display
+fn rationale_source(rationale: &Rationale) -> String {
+    match &rationale.source {
+        RationaleSource::Sidecar { path } => path.display().to_string(),
+        RationaleSource::Note { commit } => format!("git note on {}", &commit[..8]),
+    }
+}
+
+/// Group items by the ecosystem and manifest of their dependency, keeping
+/// the order manifests were found in
+fn manifest_groups<'a, T>(
+    items: &'a [T],
+    dependency: impl Fn(&'a T) -> &'a Dependency,
+) -> Vec<(EcosystemType, Option<&'a Path>, Vec<&'a T>)> {
+    let mut groups: Vec<(EcosystemType, Option<&Path>, Vec<&T>)> = Vec::new();
+    for item in items {
+        let dep = dependency(item);
+        let manifest = dep.manifest.as_deref();
+        match groups
+            .iter_mut()
+            .find(|(ecosystem, path, _)| *ecosystem == dep.ecosystem && *path == manifest)
+        {
+            Some((_, _, members)) => members.push(item),
+            None => groups.push((dep.ecosystem, manifest, vec![item])),
+        }
+    }
+    groups
+}
+
+fn print_manifest_header(ecosystem: EcosystemType, manifest: Option<&Path>) {
+    match manifest {
+        Some(manifest) => println!("## {} - {}\n", ecosystem.as_str(), manifest.display()),
+        None => println!("## {}\n", ecosystem.as_str()),
+    }
+}
+
+/// Short usage verdict for the audit; none for internal and ignored
+/// dependencies, whose annotations already say why they are not checked
+fn usage_status(analysis: &DependencyAnalysis) -> Option<String> {
+    let dep = &analysis.dependency;
+    let usage = &analysis.usage_info;
+    if dep.is_internal || dep.ignore_reason.is_some() {
+        return None;
+    }
+    Some(if !usage.is_used {
+        "UNUSED".to_string()
+    } else if usage.is_type_only() {
+        format!("used, types only ({} imports)", usage.usage_count)
+    } else if usage.is_test_only() {
+        format!("used in tests only ({} imports)", usage.usage_count)
+    } else {
+        format!("used ({} imports)", usage.usage_count)
+    })
+}
+
+fn print_dependency_sections(dependencies: &[&Dependency]) {
     let direct_deps: Vec<_> = dependencies
         .iter()
         .filter(|d| {
@@ -115,7 +434,7 @@ pub fn print_dependency_list(dependencies: &[Dependency]) {
         println!("Direct Dependencies ({}):", direct_deps.len());
         println!("{}", "-".repeat(60));
         for dep in &direct_deps {
-            println!("  {} ({})", dep.name, dep.version);
+            println!("  {} ({}){}", dep.name, dep.version, annotations(dep));
         }
         println!();
     }
@@ -124,7 +443,7 @@ pub fn print_dependency_list(dependencies: &[Dependency]) {
         println!("Development Dependencies ({}):", dev_deps.len());
         println!("{}", "-".repeat(60));
         for dep in &dev_deps {
-            println!("  {} ({})", dep.name, dep.version);
+            println!("  {} ({}){}", dep.name, dep.version, annotations(dep));
         }
         println!();
     }
@@ -134,14 +453,282 @@ pub fn print_dependency_list(dependencies: &[Dependency]) {
         println!("{}", "-".repeat(60));
         for dep in &other_deps {
             println!(
-                "  {} ({}) - {}",
+                "  {} ({}) - {}{}",
                 dep.name,
                 dep.version,
-                dep.dependency_type.as_str()
+                dep.dependency_type.as_str(),
+                annotations(dep)
             );
         }
         println!();
     }
+}
 
+/// Workspace and internal-link markers shown after a dependency in listings
+fn annotations(dep: &Dependency) -> String {
+    let mut notes = String::new();
+    if let Some(workspace) = &dep.workspace {
+        notes.push_str(&format!(" [{}]", workspace));
+    }
+    if dep.is_internal {
+        notes.push_str(" [internal]");
+    }
+    if let Some(real) = &dep.alias_for {
+        notes.push_str(&format!(" [alias of {}]", real));
+    }
+    if dep.is_bundled {
+        notes.push_str(" [bundled]");
+    }
+    if dep.ignore_reason.is_some() {
+        notes.push_str(" [ignored]");
+    }
+    notes
+}
+
+pub fn print_diff(diff: &DependencyDiff) {
+    println!("\n{}", "=".repeat(60));
+    println!(
+        "Dependency Changes: {} ({}) -> {} ({})",
+        diff.from,
+        &diff.from_commit[..8],
+        diff.to,
+        &diff.to_commit[..8]
+    );
+    println!("{}\n", "=".repeat(60));
+
+    if diff.changes.is_empty() {
+        println!("No dependency changes.\n");
+        return;
+    }
+
+    let mut current_manifest = None;
+    for change in &diff.changes {
+        if current_manifest != Some(&change.manifest) {
+            current_manifest = Some(&change.manifest);
+            match &change.manifest {
+                Some(manifest) => println!(
+                    "## {} - {}\n",
+                    change.ecosystem.as_str(),
+                    manifest.display()
+                ),
+                None => println!("## {}\n", change.ecosystem.as_str()),
+            }
+        }
+
+        println!(
+            "  {} {}: {}",
+            change.kind.as_str(),
+            change.name,
+            change_summary(change)
+        );
+        for commit in &change.commits {
+            println!(
+                "    {} {} ({})",
+                &commit.commit_hash[..8],
+                commit.message.lines().next().unwrap_or("").trim(),
+                commit.author
+            );
+        }
+        println!();
+    }
+
+    println!("{}\n", "=".repeat(60));
+}
+
+/// `1.0 -> 1.2 (locked 1.0.4 -> 1.2.1), Development -> Direct`
+pub(crate) fn change_summary(change: &DependencyChange) -> String {
+    let describe = |state: &DependencyState| match &state.locked {
+        Some(locked) => format!("{} (locked {})", state.version, locked),
+        None => state.version.clone(),
+    };
+
+    match (&change.before, &change.after) {
+        (Some(before), Some(after)) => {
+            let mut summary = if before.version != after.version || before.locked != after.locked {
+                format!("{} -> {}", describe(before), describe(after))
+            } else {
+                describe(after)
+            };
+            if before.dependency_type != after.dependency_type {
+                summary.push_str(&format!(
+                    ", {} -> {}",
+                    before.dependency_type.as_str(),
+                    after.dependency_type.as_str()
+                ));
+            }
+            summary
+        }
+        (None, Some(state)) | (Some(state), None) => {
+            format!("{} ({})", describe(state), state.dependency_type.as_str())
+        }
+        (None, None) => String::new(),
+    }
+}
+
+pub fn print_graveyard(removed: &[RemovedDependency]) {
+    println!("\n{}", "=".repeat(60));
+    println!("Removed Dependencies ({} total)", removed.len());
+    println!("{}\n", "=".repeat(60));
+
+    for entry in removed {
+        println!(
+            "{} {} ({}, {})",
+            entry.name,
+            entry.version,
+            entry.dependency_type.as_str(),
+            entry.manifest.display()
+        );
+        for (label, commit) in [("Added", &entry.added), ("Removed", &entry.removed)] {
+            println!(
+                "  {}: {} {} by {}: {}",
+                label,
+                commit_ref(commit),
+                commit.date.format("%Y-%m-%d"),
+                commit.author,
+                commit.message.lines().next().unwrap_or("").trim()
+            );
+        }
+        if let Some(reason) = &entry.removed.reason {
+            println!("  Reason: {}", reason);
+        }
+        println!("  Lived: {} days\n", entry.lifetime_days);
+    }
+
+    println!("{}\n", "=".repeat(60));
+}
+
+pub fn print_authors(authors: &[AuthorStats], scanned: bool) {
+    println!("\n{}", "=".repeat(60));
+    println!("Dependencies by Author ({} total)", authors.len());
     println!("{}\n", "=".repeat(60));
+
+    for author in authors {
+        println!("{}", author.author);
+        if scanned {
+            println!(
+                "  Introduced: {} ({} unused)",
+                author.introduced, author.unused
+            );
+        } else {
+            println!("  Introduced: {}", author.introduced);
+        }
+        println!("  Removed: {}", author.removed);
+        if !author.dependencies.is_empty() {
+            println!("  Dependencies: {}", author.dependencies.join(", "));
+        }
+        println!();
+    }
+
+    println!("{}\n", "=".repeat(60));
+}
+
+/// Tell the committer which dependencies the commit message has to explain.
+/// Hooks write to stderr, which git shows while committing.
+pub fn print_hook_notice(added: &[Dependency]) {
+    eprintln!("This commit adds dependencies: {}", dependency_names(added));
+    eprintln!(
+        "Say why in the commit message, by naming each of them or in a `{}:` trailer.",
+        hooks::REASON_TRAILER
+    );
+}
+
+/// Explain a missing reason; `enforced` when the commit is rejected for it
+pub fn print_hook_rejection(unexplained: &[&Dependency], enforced: bool) {
+    let names: Vec<&str> = unexplained.iter().map(|dep| dep.name.as_str()).collect();
+    eprintln!(
+        "The commit message gives no reason for adding: {}",
+        names.join(", ")
+    );
+    eprintln!(
+        "Name each of them in the message, or add a trailer such as\n\n    {}: <why they are needed>\n",
+        hooks::REASON_TRAILER
+    );
+    if enforced {
+        eprintln!("`git commit --no-verify` skips the check.");
+    }
+}
+
+fn dependency_names(dependencies: &[Dependency]) -> String {
+    dependencies
+        .iter()
+        .map(|dep| match &dep.manifest {
+            Some(manifest) => format!("{} ({})", dep.name, manifest.display()),
+            None => dep.name.clone(),
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Short hash of a commit, qualified when a shallow clone's history ends
+/// there and the change may be older
+fn commit_ref(git_info: &GitInfo) -> String {
+    if git_info.at_boundary {
+        format!("at or before {}", &git_info.commit_hash[..8])
+    } else {
+        git_info.commit_hash[..8].to_string()
+    }
+}
+
+fn print_shallow_note(shallow: &ShallowHistory) {
+    let boundaries: Vec<&str> = shallow.boundaries.iter().map(|b| &b[..8]).collect();
+    println!(
+        "\nNote: this is a shallow clone with {} commits of history, ending at {}.",
+        shallow.depth,
+        boundaries.join(", ")
+    );
+    println!(
+        "Commits marked \"at or before\" may not be where the change was made. Run \
+         `git fetch --deepen={}` for more history, or `git fetch --unshallow` for all of it.",
+        shallow.depth
+    );
+}
+
+/// What was extracted from a commit message: the reason, Conventional
+/// Commits type, issue references and trailers
+fn print_message_details(git_info: &GitInfo) {
+    if let Some(reason) = &git_info.reason {
+        println!("\nReason: {}", reason);
+    }
+    if let Some(conventional) = &git_info.conventional {
+        let scope = conventional
+            .scope
+            .as_ref()
+            .map(|scope| format!("({})", scope))
+            .unwrap_or_default();
+        let breaking = if conventional.breaking {
+            " (breaking)"
+        } else {
+            ""
+        };
+        println!("Commit type: {}{}{}", conventional.kind, scope, breaking);
+    }
+    if !git_info.issues.is_empty() {
+        let issues: Vec<String> = git_info
+            .issues
+            .iter()
+            .map(|issue| match &issue.url {
+                Some(url) if *url != issue.key => format!("{} ({})", issue.key, url),
+                _ => issue.key.clone(),
+            })
+            .collect();
+        println!("Issues: {}", issues.join(", "));
+    }
+    for trailer in &git_info.trailers {
+        println!("{}: {}", trailer.key, trailer.value);
+    }
+}
+
+/// `#123 Title (from branch)`, leaving out the parts that are not known
+fn pull_request_summary(pull_request: &PullRequest) -> String {
+    let mut parts = Vec::new();
+    if let Some(number) = pull_request.number {
+        parts.push(format!("#{}", number));
+    }
+    if let Some(title) = &pull_request.title {
+        parts.push(title.clone());
+    }
+    if let Some(branch) = &pull_request.branch {
+        parts.push(format!("(from {})", branch));
+    }
+    parts.join(" ")
 }
@@ -0,0 +1,165 @@
+use regex::Regex;
+use std::sync::OnceLock;
+
+/// Locate a dependency's entry in the text of a manifest, see
+/// [`EntryPattern::lines`]
+pub fn entry_lines(content: &str, name: &str) -> Option<(usize, usize)> {
+    EntryPattern::new(name).lines(content)
+}
+
+/// The patterns that find one dependency's entry in manifests, compiled
+/// once for all the revisions of a manifest that are searched
+#[derive(Debug, Clone)]
+pub struct EntryPattern {
+    key: Regex,
+    table: Regex,
+    mention: Regex,
+}
+
+impl EntryPattern {
+    pub fn new(name: &str) -> Self {
+        let name = regex::escape(name);
+        let regex = |pattern: String| Regex::new(&pattern).expect("escaped name is a valid regex");
+        Self {
+            key: regex(format!(
+                r#"^\s*(?:require\s+)?["']?{}["']?\s*(?:[=:<>!~;@\[,\s]|$)"#,
+                name
+            )),
+            table: regex(format!(r#"^\s*\[[^\]]*\.["']?{}["']?\]\s*$"#, name)),
+            mention: regex(format!(r"(?:^|[^\w@./-]){}(?:$|[^\w./-])", name)),
+        }
+    }
+
+    /// Locate the dependency's entry in the text of a manifest.
+    ///
+    /// Returns the 1-based, inclusive line range of the entry: a single line
+    /// for `serde = "1"`, `"react": "^18"` or `requests>=2`, and several for a
+    /// multi-line inline table, a JSON object or a `[dependencies.serde]` table.
+    ///
+    /// A key-like entry (`name = `, `"name":`, `name>=`, `require name v1`) in a
+    /// dependency section wins over one elsewhere, which wins over the name
+    /// merely being mentioned in a dependency section, such as in a PEP 621
+    /// `dependencies = ["name"]` array.
+    pub fn lines(&self, content: &str) -> Option<(usize, usize)> {
+        static TOML_HEADER: OnceLock<Regex> = OnceLock::new();
+        static JSON_OBJECT: OnceLock<Regex> = OnceLock::new();
+        static GO_BLOCK: OnceLock<Regex> = OnceLock::new();
+        let toml_header =
+            TOML_HEADER.get_or_init(|| Regex::new(r"^\s*\[\[?([^\]]+)\]").expect("valid regex"));
+        let json_object = JSON_OBJECT
+            .get_or_init(|| Regex::new(r#"^\s*"([^"]+)"\s*:\s*\{"#).expect("valid regex"));
+        let go_block =
+            GO_BLOCK.get_or_init(|| Regex::new(r"^\s*(require)\s*\(").expect("valid regex"));
+
+        let lines: Vec<&str> = content.lines().collect();
+        let mut section = String::new();
+        let mut key_elsewhere = None;
+        let mut mentioned = None;
+
+        for (index, line) in lines.iter().enumerate() {
+            if self.table.is_match(line) {
+                return Some((index + 1, table_end(&lines, index)));
+            }
+
+            let in_dependencies = is_dependency_section(&section);
+            if self.key.is_match(line) {
+                let range = (index + 1, value_end(&lines, index));
+                if in_dependencies {
+                    return Some(range);
+                }
+                key_elsewhere.get_or_insert(range);
+            } else if in_dependencies && self.mention.is_match(line) {
+                mentioned.get_or_insert((index + 1, index + 1));
+            }
+
+            if let Some(captures) = toml_header
+                .captures(line)
+                .or_else(|| json_object.captures(line))
+                .or_else(|| go_block.captures(line))
+            {
+                section = captures[1].to_lowercase();
+            }
+        }
+
+        key_elsewhere.or(mentioned)
+    }
+}
+
+fn is_dependency_section(section: &str) -> bool {
+    ["dependencies", "imports", "require", "project"]
+        .iter()
+        .any(|marker| section.contains(marker))
+}
+
+/// Last line of a TOML table starting at `start`, before the next header,
+/// leaving out trailing blank lines and comments
+fn table_end(lines: &[&str], start: usize) -> usize {
+    let mut end = start;
+    for (index, line) in lines.iter().enumerate().skip(start + 1) {
+        let trimmed = line.trim();
+        if trimmed.starts_with('[') {
+            break;
+        }
+        if !trimmed.is_empty() && !trimmed.starts_with('#') {
+            end = index;
+        }
+    }
+    end + 1
+}
+
+/// Last line of a value starting at `start`, following unclosed braces and
+/// brackets outside of strings onto the next lines
+fn value_end(lines: &[&str], start: usize) -> usize {
+    let mut depth = 0i32;
+    for (index, line) in lines.iter().enumerate().skip(start) {
+        let mut quote = None;
+        let mut escaped = false;
+        for c in line.chars() {
+            match quote {
+                Some(_) if escaped => escaped = false,
+                Some(_) if c == '\\' => escaped = true,
+                Some(q) if c == q => quote = None,
+                Some(_) => {}
+                None => match c {
+                    '"' | '\'' => quote = Some(c),
+                    '{' | '[' => depth += 1,
+                    '}' | ']' => depth -= 1,
+                    '#' => break,
+                    _ => {}
+                },
+            }
+        }
+        if depth <= 0 {
+            return index + 1;
+        }
+    }
+    lines.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn one_line_entries() {
+        let cargo = "[package]\nname = \"serde\"\n\n[dependencies]\nserde = \"1\"\n";
+        assert_eq!(entry_lines(cargo, "serde"), Some((5, 5)));
+        let package = "{\n  \"dependencies\": {\n    \"react\": \"^18\"\n  }\n}";
+        assert_eq!(entry_lines(package, "react"), Some((3, 3)));
+        assert_eq!(entry_lines("requests>=2\nflask\n", "flask"), Some((2, 2)));
+    }
+
+    #[test]
+    fn multi_line_entries() {
+        let inline = "[dependencies]\nserde = { version = \"1\", features = [\n    \"derive\",\n] }\nlog = \"0.4\"\n";
+        assert_eq!(entry_lines(inline, "serde"), Some((2, 4)));
+        let table = "[dependencies.serde]\nversion = \"1\"\n\n# comment\n[dev-dependencies]\n";
+        assert_eq!(entry_lines(table, "serde"), Some((1, 2)));
+    }
+
+    #[test]
+    fn longer_names_are_not_entries() {
+        let cargo = "[dependencies]\nserde_json = \"1\"\n";
+        assert_eq!(entry_lines(cargo, "serde"), None);
+    }
+}
@@ -1,2 +1,4 @@
 pub mod fs;
+pub mod jsonc;
+pub mod manifest;
 pub mod patterns;
@@ -1,2 +1,156 @@
-// Placeholder for common regex patterns
-// Can be used for shared pattern matching logic in the future
+use regex::Regex;
+use std::path::Path;
+
+/// Compile a path glob into a regex matching `/`-separated relative paths.
+///
+/// Supports `*` (any characters within a path segment), `?` (one character
+/// within a segment) and `**` (any number of segments).
+pub fn glob_to_regex(glob: &str) -> Result<Regex, regex::Error> {
+    let glob = glob.trim_start_matches("./").trim_end_matches('/');
+    let mut pattern = String::from("^");
+    let mut chars = glob.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                // `**/` also matches zero segments
+                if chars.peek() == Some(&'/') {
+                    chars.next();
+                    pattern.push_str("(?:.*/)?");
+                } else {
+                    pattern.push_str(".*");
+                }
+            }
+            '*' => pattern.push_str("[^/]*"),
+            '?' => pattern.push_str("[^/]"),
+            c => pattern.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+
+    pattern.push('$');
+    Regex::new(&pattern)
+}
+
+/// A set of globs matched against `/`-separated relative paths.
+///
+/// Like in `.gitignore`, a glob without a `/` matches a file or directory
+/// name at any depth, and a glob matching a directory matches everything in it.
+#[derive(Debug, Clone, Default)]
+pub struct PathGlobs {
+    paths: Vec<Regex>,
+    names: Vec<Regex>,
+}
+
+impl PathGlobs {
+    /// Compile the globs, skipping any that are invalid
+    pub fn new<S: AsRef<str>>(globs: &[S]) -> Self {
+        let mut set = Self::default();
+        for glob in globs {
+            let glob = glob.as_ref().trim_end_matches('/');
+            if let Ok(re) = glob_to_regex(glob) {
+                if glob.contains('/') {
+                    set.paths.push(re);
+                } else {
+                    set.names.push(re);
+                }
+            }
+        }
+        set
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.paths.is_empty() && self.names.is_empty()
+    }
+
+    /// Check a path relative to the directory the globs are anchored at
+    pub fn is_match(&self, relative: &Path) -> bool {
+        relative.ancestors().any(|path| {
+            let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+            let path = path.to_str().unwrap_or("").replace('\\', "/");
+            (!path.is_empty() && self.paths.iter().any(|re| re.is_match(&path)))
+                || (!name.is_empty() && self.names.iter().any(|re| re.is_match(name)))
+        })
+    }
+}
+
+/// A set of globs matched against whole names, such as dependency names or
+/// emails, rather than paths
+#[derive(Debug, Clone, Default)]
+pub struct NameGlobs {
+    patterns: Vec<Regex>,
+}
+
+impl NameGlobs {
+    /// Compile the globs, skipping any that are invalid
+    pub fn new<S: AsRef<str>>(globs: &[S]) -> Self {
+        Self {
+            patterns: globs
+                .iter()
+                .filter_map(|glob| glob_to_regex(glob.as_ref()).ok())
+                .collect(),
+        }
+    }
+
+    pub fn is_match(&self, name: &str) -> bool {
+        self.patterns.iter().any(|re| re.is_match(name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matches(glob: &str, path: &str) -> bool {
+        glob_to_regex(glob).unwrap().is_match(path)
+    }
+
+    #[test]
+    fn single_star_stays_within_a_segment() {
+        assert!(matches("src/*.ts", "src/a.ts"));
+        assert!(!matches("src/*.ts", "src/lib/a.ts"));
+        assert!(matches("*.test.*", "a.test.js"));
+        assert!(!matches("*.ts", "a.tsx"));
+    }
+
+    #[test]
+    fn double_star_spans_segments() {
+        assert!(matches("src/**/*.ts", "src/a.ts"));
+        assert!(matches("src/**/*.ts", "src/lib/deep/a.ts"));
+        assert!(matches("**/fixtures", "fixtures"));
+        assert!(matches("vendor/**", "vendor/a/b"));
+        assert!(!matches("src/**/*.ts", "lib/a.ts"));
+    }
+
+    #[test]
+    fn question_mark_and_literals() {
+        assert!(matches("v?.x", "v1.x"));
+        assert!(!matches("v?.x", "v1/x"));
+        // Regex metacharacters are matched literally
+        assert!(!matches("v?.x", "v1ax"));
+        assert!(matches("@types/*", "@types/react"));
+        assert!(matches("(a)+", "(a)+"));
+    }
+
+    #[test]
+    fn leading_dot_slash_and_trailing_slash_are_ignored() {
+        assert!(matches("./build/", "build"));
+    }
+
+    #[test]
+    fn path_globs_match_names_at_any_depth() {
+        let globs = PathGlobs::new(&["tests", "src/gen/*"]);
+        assert!(globs.is_match(Path::new("tests/a.rs")));
+        assert!(globs.is_match(Path::new("crates/x/tests/a.rs")));
+        assert!(globs.is_match(Path::new("src/gen/a.ts")));
+        assert!(!globs.is_match(Path::new("lib/src/gen/a.ts")));
+    }
+
+    #[test]
+    fn name_globs_match_whole_names() {
+        let globs = NameGlobs::new(&["@types/*", "eslint*"]);
+        assert!(globs.is_match("@types/node"));
+        assert!(globs.is_match("eslint-plugin-react"));
+        assert!(!globs.is_match("typescript-eslint"));
+    }
+}